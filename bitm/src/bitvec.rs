@@ -1,5 +1,5 @@
-use std::{iter::FusedIterator, ops::Range};
-use super::{ceiling_div, n_lowest_bits, n_lowest_bits_1_64};
+use std::{collections::TryReserveError, iter::FusedIterator, ops::{Deref, Range}};
+use super::{ceiling_div, n_lowest_bits, n_lowest_bits_0_64, n_lowest_bits_1_64, select64};
 
 /// Iterator over indices of bits set to 1 (if `B` is `true`) or 0 (if `B` is `false`) in slice of `u64`.
 pub struct BitBIterator<'a, const B: bool> {
@@ -8,12 +8,24 @@ pub struct BitBIterator<'a, const B: bool> {
     /// 64 * index of the current segment.
     first_segment_bit: usize,
     /// Copy of the current segment (or its negation if `!B`) with zeroed already exposed bits.
-    current_segment: u64
+    current_segment: u64,
+    /// 64 * index of the back segment.
+    back_segment_first_bit: usize,
+    /// Copy of the back segment (or its negation if `!B`) with zeroed already exposed (by `next_back`) bits.
+    /// It is `0` if no segment is taken from the back of `segment_iter`.
+    back_segment: u64
 }
 
 impl<'a, const B: bool> BitBIterator<'a, B> {
     /// Constructs iterator over bits set in the given `slice`.
-    pub fn new(slice: &'a [u64]) -> Self {
+    #[inline] pub fn new(slice: &'a [u64]) -> Self {
+        Self::with_segment_offset(slice, 0)
+    }
+
+    /// Constructs iterator over bits set in the given `slice`, which is assumed to begin
+    /// at the segment with index `first_segment` of a larger bit vector.
+    /// So the returned indices are relative to the beginning of the larger vector, i.e. offset by `64*first_segment`.
+    pub fn with_segment_offset(slice: &'a [u64], first_segment: usize) -> Self {
         let mut segment_iter = slice.into_iter();
         let current_segment = if B {
             segment_iter.next().copied().unwrap_or(0)
@@ -22,8 +34,10 @@ impl<'a, const B: bool> BitBIterator<'a, B> {
         };
         Self {
             segment_iter,
-            first_segment_bit: 0,
-            current_segment
+            first_segment_bit: first_segment * 64,
+            current_segment,
+            back_segment_first_bit: (first_segment + slice.len()) * 64,
+            back_segment: 0
         }
     }
 }
@@ -33,8 +47,14 @@ impl<'a, const B: bool> Iterator for BitBIterator<'a, B> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while self.current_segment == 0 {
-            self.current_segment = if B { *self.segment_iter.next()? } else { !*self.segment_iter.next()? };
-            self.first_segment_bit += 64;
+            if let Some(segment) = self.segment_iter.next() {
+                self.current_segment = if B { *segment } else { !*segment };
+                self.first_segment_bit += 64;
+            } else {    // only the back segment remains
+                if self.back_segment == 0 { return None; }
+                self.current_segment = std::mem::take(&mut self.back_segment);
+                self.first_segment_bit = self.back_segment_first_bit;
+            }
         }
         let result = self.current_segment.trailing_zeros();
         self.current_segment ^= 1<<result;
@@ -47,12 +67,31 @@ impl<'a, const B: bool> Iterator for BitBIterator<'a, B> {
     }
 }
 
+impl<'a, const B: bool> DoubleEndedIterator for BitBIterator<'a, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.back_segment == 0 {
+            if let Some(segment) = self.segment_iter.next_back() {
+                self.back_segment = if B { *segment } else { !*segment };
+                self.back_segment_first_bit -= 64;
+            } else {    // only the current segment remains
+                if self.current_segment == 0 { return None; }
+                self.back_segment = std::mem::take(&mut self.current_segment);
+                self.back_segment_first_bit = self.first_segment_bit;
+            }
+        }
+        let result = 63 - self.back_segment.leading_zeros();
+        self.back_segment ^= 1<<result;
+        Some(self.back_segment_first_bit + (result as usize))
+    }
+}
+
 impl<'a, const B: bool> ExactSizeIterator for BitBIterator<'a, B> {
     #[inline] fn len(&self) -> usize {
-        if B {
-            self.current_segment.count_ones() as usize + self.segment_iter.as_slice().count_bit_ones()
+        // note that current_segment and back_segment are already negated if !B
+        self.current_segment.count_ones() as usize + self.back_segment.count_ones() as usize + if B {
+            self.segment_iter.as_slice().count_bit_ones()
         } else {
-            self.current_segment.count_zeros() as usize + self.segment_iter.as_slice().count_bit_zeros()
+            self.segment_iter.as_slice().count_bit_zeros()
         }
     }
 }
@@ -63,8 +102,58 @@ impl<'a, const B: bool> FusedIterator for BitBIterator<'a, B> where std::slice::
 pub type BitOnesIterator<'a> = BitBIterator<'a, true>;
 
 /// Iterator over bits set to 0 in slice of `u64`.
+///
+/// All 64 bits of each segment are considered, including the (usually unused) tail of the last segment.
 pub type BitZerosIterator<'a> = BitBIterator<'a, false>;
 
+/// Iterator over maximal runs of consecutive bits set to 1 in slice of `u64`.
+/// It yields pairs (index of the first bit of the run, length of the run).
+///
+/// Runs that span segment boundaries are yielded as single pairs.
+pub struct BitRunsIterator<'a> {
+    /// Slice of 64-bit segments.
+    segments: &'a [u64],
+    /// Index of the current segment.
+    segment_index: usize,
+    /// Copy of the current segment with zeroed already exposed bits.
+    current_segment: u64
+}
+
+impl<'a> BitRunsIterator<'a> {
+    /// Constructs iterator over runs of ones in the given `slice`.
+    pub fn new(slice: &'a [u64]) -> Self {
+        Self { segments: slice, segment_index: 0, current_segment: slice.first().copied().unwrap_or(0) }
+    }
+}
+
+impl<'a> Iterator for BitRunsIterator<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_segment == 0 {
+            self.segment_index += 1;
+            self.current_segment = *self.segments.get(self.segment_index)?;
+        }
+        let start_in_segment = self.current_segment.trailing_zeros() as u8;
+        let start = self.segment_index * 64 + start_in_segment as usize;
+        // the first zero not lower than start_in_segment:
+        let mut end_in_segment = (!(self.current_segment | n_lowest_bits(start_in_segment))).trailing_zeros() as u8;
+        while end_in_segment == 64 {    // the run continues in the next segment
+            self.segment_index += 1;
+            let Some(segment) = self.segments.get(self.segment_index) else {
+                self.current_segment = 0;
+                return Some((start, self.segment_index * 64 - start));
+            };
+            self.current_segment = *segment;
+            end_in_segment = (!self.current_segment).trailing_zeros() as u8;
+        }
+        self.current_segment &= !n_lowest_bits(end_in_segment);
+        Some((start, self.segment_index * 64 + end_in_segment as usize - start))
+    }
+}
+
+impl<'a> FusedIterator for BitRunsIterator<'a> {}
+
 
 
 /// Iterator over bits in slice of `u64`. It yields `true` for bit 1 and `false` for 0.
@@ -130,8 +219,91 @@ impl<'a> ExactSizeIterator for BitIterator<'a> where Range<usize>: ExactSizeIter
 impl<'a> FusedIterator for BitIterator<'a> where Range<usize>: FusedIterator {}
 
 
+/// Iterator over consecutive fragments of the same bit size, stored in slice of `u64`.
+pub struct FragmentsIterator<'bv> {
+    bit_vec: &'bv [u64],
+    /// Index of the first bit of the next fragment.
+    bit_nr: usize,
+    /// Size of each fragment in bits.
+    v_size: u8,
+    /// Number of fragments to be yielded.
+    remaining: usize
+}
+
+impl<'bv> FragmentsIterator<'bv> {
+    /// Constructs iterator over the first `count` fragments of `v_size` bits each, stored in `bit_vec`.
+    #[inline] pub fn new(bit_vec: &'bv [u64], v_size: u8, count: usize) -> Self {
+        assert!(count * v_size as usize <= bit_vec.len() * 64, "FragmentsIterator fragments out of bounds.");
+        Self { bit_vec, bit_nr: 0, v_size, remaining: count }
+    }
+}
+
+impl<'bv> Iterator for FragmentsIterator<'bv> {
+    type Item = u64;
+
+    #[inline] fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+        let result = unsafe { self.bit_vec.get_bits_unchecked(self.bit_nr, self.v_size) };
+        self.bit_nr += self.v_size as usize;
+        Some(result)
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'bv> ExactSizeIterator for FragmentsIterator<'bv> {
+    #[inline] fn len(&self) -> usize { self.remaining }
+}
+
+impl<'bv> FusedIterator for FragmentsIterator<'bv> {}
+
+/// Iterator over consecutive fragments of the same bit size, which owns (and consumes) the bit vector `B` that stores them.
+pub struct IntoFragmentsIterator<B = Box<[u64]>> {
+    bit_vec: B,
+    /// Index of the first bit of the next fragment.
+    bit_nr: usize,
+    /// Size of each fragment in bits.
+    v_size: u8,
+    /// Number of fragments to be yielded.
+    remaining: usize
+}
+
+impl<B: Deref<Target = [u64]>> IntoFragmentsIterator<B> {
+    /// Constructs iterator over the first `count` fragments of `v_size` bits each, stored in `bit_vec`.
+    #[inline] pub fn new(bit_vec: B, v_size: u8, count: usize) -> Self {
+        assert!(count * v_size as usize <= bit_vec.len() * 64, "IntoFragmentsIterator fragments out of bounds.");
+        Self { bit_vec, bit_nr: 0, v_size, remaining: count }
+    }
+}
+
+impl<B: Deref<Target = [u64]>> Iterator for IntoFragmentsIterator<B> {
+    type Item = u64;
+
+    #[inline] fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+        let result = unsafe { self.bit_vec.get_bits_unchecked(self.bit_nr, self.v_size) };
+        self.bit_nr += self.v_size as usize;
+        Some(result)
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<B: Deref<Target = [u64]>> ExactSizeIterator for IntoFragmentsIterator<B> {
+    #[inline] fn len(&self) -> usize { self.remaining }
+}
+
+impl<B: Deref<Target = [u64]>> FusedIterator for IntoFragmentsIterator<B> {}
+
+
 /// The trait that is implemented for the array of `u64` and extends it with methods for
-/// accessing and modifying single bits or arbitrary fragments consisted of few (up to 63) bits.
+/// accessing and modifying single bits or arbitrary fragments consisted of few (up to 64) bits.
 pub trait BitAccess {
     /// Gets bit with given index `bit_nr`. Panics if `bit_nr` is out of bounds.
     fn get_bit(&self, bit_nr: usize) -> bool;
@@ -213,16 +385,21 @@ pub trait BitAccess {
     /// Gets bits `[begin, begin+len)`. Panics if the range is out of bounds.
     #[inline] fn get_bits(&self, begin: usize, len: u8) -> u64 {
         //if len == 0 { return 0; }
-        self.get_bits_unmasked(begin, len) & n_lowest_bits(len)
+        self.get_bits_unmasked(begin, len) & n_lowest_bits_0_64(len)
     }
 
     /// Gets at least `len` bits beginning from the bit index `begin`.
     /// Returns [`None`] if the range is out of bounds.
     fn try_get_bits_unmasked(&self, begin: usize, len: u8) -> Option<u64>;
 
+    /// Gets bits `[begin, begin+max_len)`, like [`Self::get_bits`], but the bits beyond the end of `self`
+    /// are not read and are returned as zeros (also if `begin` is out of bounds).
+    /// Useful for reading a window of (up to 64) bits, that can exceed the end of `self`.
+    fn peek_bits(&self, begin: usize, max_len: u8) -> u64;
+
     /// Gets bits `[begin, begin+len)`. Returns [`None`] if the range is out of bounds.
     #[inline(always)] fn try_get_bits(&self, begin: usize, len: u8) -> Option<u64> {
-        self.try_get_bits_unmasked(begin, len).map(|result| result & n_lowest_bits(len))
+        self.try_get_bits_unmasked(begin, len).map(|result| result & n_lowest_bits_0_64(len))
     }
 
     /// Gets at least `len` bits beginning from the bit index `begin` without bounds checking.
@@ -230,7 +407,7 @@ pub trait BitAccess {
 
     /// Gets bits `[begin, begin+len)` without bounds checking.
     #[inline(always)] unsafe fn get_bits_unchecked(&self, begin: usize, len: u8) -> u64 {
-        self.get_bits_unmasked_unchecked(begin, len) & n_lowest_bits(len)
+        self.get_bits_unmasked_unchecked(begin, len) & n_lowest_bits_0_64(len)
     }
 
     /// Gets bits `[begin, begin+len)` and increase `bit_nr` by `len`.
@@ -257,6 +434,28 @@ pub trait BitAccess {
     /// Sets bits `[begin, begin+len)` to the content of `v`. Panics if the range is out of bounds.
     fn set_bits(&mut self, begin: usize, v: u64, len: u8);
 
+    /// Sets bits `[begin, begin+len)` to the content of `v`.
+    /// Returns [`None`] (and leaves `self` unchanged) if the range is out of bounds.
+    fn try_set_bits(&mut self, begin: usize, v: u64, len: u8) -> Option<()>;
+
+    /// Gets bits `[begin, begin+len)`, where `len` is in range [0, 128]. Panics if the range is out of bounds.
+    /// The bits can span up to three segments.
+    #[inline] fn get_bits_u128(&self, begin: usize, len: u8) -> u128 {
+        debug_assert!(len <= 128, "get_bits_u128: len {len} exceeds 128");
+        if len <= 64 { return self.get_bits(begin, len) as u128; }
+        self.get_bits(begin, 64) as u128 | (self.get_bits(begin + 64, len - 64) as u128) << 64
+    }
+
+    /// Sets bits `[begin, begin+len)`, where `len` is in range [0, 128], to the content of `v`.
+    /// Panics if the range is out of bounds.
+    /// The bits can span up to three segments.
+    #[inline] fn set_bits_u128(&mut self, begin: usize, v: u128, len: u8) {
+        debug_assert!(len <= 128, "set_bits_u128: len {len} exceeds 128");
+        if len <= 64 { return self.set_bits(begin, v as u64, len); }
+        self.set_bits(begin, v as u64, 64);
+        self.set_bits(begin + 64, (v >> 64) as u64, len - 64);
+    }
+
     /// Sets bits `[begin, begin+len)` to the content of `v` and increase `begin` by `len`. Panics if the range is out of bounds.
     #[inline] fn set_successive_bits(&mut self, begin: &mut usize, v: u64, len: u8) {
         self.set_bits(*begin, v, len);  *begin += len as usize;
@@ -265,7 +464,40 @@ pub trait BitAccess {
     /// Sets bits `[begin, begin+len)` to the content of `v`, without bounds checking.
     unsafe fn set_bits_unchecked(&mut self, begin: usize, v: u64, len: u8);
 
-    /// Xor at least `len` bits of `self`, staring from index `begin`, with `v`. Panics if the range is out of bounds.
+    /// Copies `len` bits of `src`, beginning from `src_begin`, to `self`, beginning from `dst_begin`.
+    /// The bits are copied a word (64 bits) at a time.
+    /// `self` and `src` cannot overlap, as guaranteed by the borrowing rules.
+    /// Panics if any range is out of bounds.
+    fn copy_bits_from(&mut self, dst_begin: usize, src: &[u64], src_begin: usize, len: usize) {
+        for offset in (0..len).step_by(64) {
+            let chunk_len = (len - offset).min(64) as u8;
+            self.set_bits(dst_begin + offset, src.get_bits(src_begin + offset, chunk_len), chunk_len);
+        }
+    }
+
+    /// Reverses the order of bits in range `[begin, end)`.
+    /// Panics if `begin > end` or the range is out of bounds.
+    fn reverse_bit_range(&mut self, mut begin: usize, mut end: usize) {
+        assert!(begin <= end, "begin > end in reverse_bit_range");
+        // swaps reversed chunks (of up to 64 bits) from both ends of the range, moving towards its middle
+        while end - begin >= 2 {
+            let len = ((end - begin) / 2).min(64) as u8;
+            let shift = 64 - len as u32;
+            let front = self.get_bits(begin, len).reverse_bits() >> shift;
+            let back = self.get_bits(end - len as usize, len).reverse_bits() >> shift;
+            self.set_bits(begin, back, len);
+            self.set_bits(end - len as usize, front, len);
+            begin += len as usize;
+            end -= len as usize;
+        }
+    }
+
+    /// Sets all bits in range `[begin, end)` to `value` (`1` if `true`, `0` otherwise).
+    /// Panics if `begin > end` or the range is out of bounds.
+    fn set_bits_to(&mut self, begin: usize, end: usize, value: bool);
+
+    /// Xor at least `len` (in range [0, 64]) bits of `self`, staring from index `begin`, with `v`.
+    /// Panics if the range is out of bounds.
     fn xor_bits(&mut self, begin: usize, v: u64, len: u8);
 
     /// Xor at least `len` bits of `self`, staring from index `begin`, with `v` and increase `begin` by `len`.
@@ -274,16 +506,82 @@ pub trait BitAccess {
         self.xor_bits(*begin, v, len);  *begin += len as usize;
     }
 
+    /// Sets each bit of `self` to the result of `and` of it and the corresponding bit of `other`.
+    /// `self` and `other` must have the same length.
+    fn and_assign(&mut self, other: &[u64]);
+
+    /// Sets each bit of `self` to the result of `or` of it and the corresponding bit of `other`.
+    /// `self` and `other` must have the same length.
+    fn or_assign(&mut self, other: &[u64]);
+
+    /// Sets each bit of `self` to the result of `xor` of it and the corresponding bit of `other`.
+    /// `self` and `other` must have the same length.
+    fn xor_assign(&mut self, other: &[u64]);
+
     /// Returns the number of zeros (cleared bits).
     fn count_bit_zeros(&self) -> usize;
 
     /// Returns the number of ones (set bits).
     fn count_bit_ones(&self) -> usize;
 
+    /// Returns the number of ones (set bits) among the first `bit_len` bits (padding bits beyond `bit_len` are ignored).
+    /// Panics if `bit_len` is out of bounds.
+    #[inline] fn count_bit_ones_in(&self, bit_len: usize) -> usize {
+        self.count_ones_in_range(0, bit_len)
+    }
+
+    /// Returns the number of zeros (cleared bits) among the first `bit_len` bits (padding bits beyond `bit_len` are ignored).
+    /// Panics if `bit_len` is out of bounds.
+    #[inline] fn count_bit_zeros_in(&self, bit_len: usize) -> usize {
+        bit_len - self.count_bit_ones_in(bit_len)
+    }
+
+    /// Returns the number of ones (set bits) in the range `[begin, end)`.
+    /// Panics if `begin > end` or `end` is out of bounds.
+    fn count_ones_in_range(&self, begin: usize, end: usize) -> usize;
+
+    /// Returns the number of ones (set bits) before the bit with given index `bit_nr`, i.e. in the range `[0, bit_nr)`.
+    /// Panics if `bit_nr` is out of bounds.
+    #[inline] fn rank_ones(&self, bit_nr: usize) -> usize {
+        self.count_ones_in_range(0, bit_nr)
+    }
+
+    /// Returns the index of the `rank`-th (counting from 0) one (set bit),
+    /// or [`None`] if there are no more than `rank` ones.
+    fn select_ones(&self, rank: usize) -> Option<usize>;
+
+    /// Returns the numbers of ones (set bits) in ranges `[0, mid)` and `[mid, end)`, calculated in one pass.
+    /// Panics if `mid > end` or `end` is out of bounds.
+    fn split_count_ones(&self, mid: usize, end: usize) -> (usize, usize);
+
+    /// Returns whether `self` and `other` have no ones (set bits) in common, i.e. whether `self & other == 0`.
+    /// Both bit vectors should be of the same length.
+    fn is_disjoint(&self, other: &[u64]) -> bool;
+
+    /// Returns the number of bits that differ between `self` and `other`, i.e. the number of ones in `self ^ other`.
+    /// Panics if the bit vectors are of different lengths.
+    fn hamming_distance(&self, other: &[u64]) -> usize;
+
+    /// Returns whether the first `bit_len` bits of `self` and `other` are equal.
+    /// The bits beyond `bit_len` (in the last segment) are ignored.
+    /// Panics if `bit_len` exceeds the length of either bit vector.
+    fn equals_bits(&self, other: &[u64], bit_len: usize) -> bool;
+
     /// Returns iterator over indices of ones (set bits).
     fn bit_ones(&'_ self) -> BitOnesIterator<'_>;
 
-    /// Returns iterator over indices of ones (set bits).
+    /// Returns iterator over indices of ones (set bits) in the segments with indices in range [`seg_begin`, `seg_end`).
+    /// The indices are relative to the beginning of `self`, so ranges of segments can be scanned independently
+    /// (e.g. by different threads). Panics if the range is out of bounds.
+    fn bit_ones_in_segments(&'_ self, seg_begin: usize, seg_end: usize) -> BitOnesIterator<'_>;
+
+    /// Returns iterator over maximal runs of consecutive ones, that yields pairs (index of the first one, length of the run).
+    fn bit_one_runs(&'_ self) -> BitRunsIterator<'_>;
+
+    /// Returns iterator over indices of zeros (cleared bits).
+    ///
+    /// As the bit vector has no explicit length, all bits of the last 64-bit segment
+    /// (also the ones beyond the length used by the caller) are considered, like in [`Self::count_bit_zeros`].
     fn bit_zeros(&'_ self) -> BitZerosIterator<'_>;
 
     /// Returns iterator over all bits in `self` that yields `true` for each one and `false` for each zero.
@@ -296,6 +594,11 @@ pub trait BitAccess {
     /// that yields `true` for each one and `false` for each zero.
     unsafe fn bit_in_unchecked_range_iter(&'_ self, bit_range: Range<usize>) -> BitIterator<'_>;
 
+    /// Returns iterator over the first `count` fragments of `v_size` bits each,
+    /// i.e. yielding [`Self::get_fragment`] for indices in range [0, `count`).
+    /// Panics if the fragments are out of bounds.
+    fn fragments(&'_ self, v_size: u8, count: usize) -> FragmentsIterator<'_>;
+
     /// Gets `index`-th fragment of at least `v_size` bits, i.e. bits with indices in range [`index*v_size`, `index*v_size+v_size`).
     /// Panics if the range is out of bounds.
     #[inline(always)] fn get_fragment_unmasked(&self, index: usize, v_size: u8) -> u64 {
@@ -314,6 +617,20 @@ pub trait BitAccess {
         self.try_get_bits_unmasked(index * v_size as usize, v_size)
     }
 
+    /// Gets `index`-th fragment of `v_size` bits from the vector constructed by [`BitVec::with_byte_aligned_fragments`],
+    /// i.e. bits with indices in range [`index*w`, `index*w+v_size`), where `w` is `v_size` rounded up to a multiple of 8.
+    /// Panics if the range is out of bounds.
+    #[inline(always)] fn get_fragment_aligned(&self, index: usize, v_size: u8) -> u64 {
+        self.get_bits(index * byte_aligned_width(v_size) as usize, v_size)
+    }
+
+    /// Gets `index`-th fragment of `bytes` (in range [1, 8]) whole bytes,
+    /// i.e. bits with indices in range [`index*bytes*8`, `index*bytes*8+bytes*8`).
+    /// Equivalent to [`Self::get_fragment`] with `v_size = bytes*8`, but faster,
+    /// as on little-endian machines it reads the bytes of the storage directly, without shifting.
+    /// Panics if the range is out of bounds.
+    fn get_byte_fragment(&self, index: usize, bytes: u8) -> u64;
+
     /// Gets `index`-th fragment of `v_size` bits, i.e. bits with indices in range [`index*v_size`, `index*v_size+v_size`).
     /// Returns [`None`] if the range is out of bounds.
     #[inline(always)] fn try_get_fragment(&self, index: usize, v_size: u8) -> Option<u64> {
@@ -339,12 +656,32 @@ pub trait BitAccess {
         result
     }
 
+    /// Sets each `out[i]` to [`Self::get_fragment`]`(indices[i], v_size)`.
+    /// Panics if `indices` and `out` differ in length or any fragment is out of bounds.
+    fn gather_fragments(&self, indices: &[usize], v_size: u8, out: &mut [u64]) {
+        assert_eq!(indices.len(), out.len(), "gather_fragments: indices and out differ in length");
+        for (o, index) in out.iter_mut().zip(indices) {
+            *o = self.get_fragment(*index, v_size);
+        }
+    }
+
     /// Initializes `index`-th fragment of `v_size` bits, i.e. bits with indices in range [`index*v_size`, `index*v_size+v_size`), to `v`.
     /// Panics if the range is out of bounds. Before initialization, the bits are assumed to be cleared or already set to `v`.
     #[inline(always)] fn init_fragment(&mut self, index: usize, v: u64, v_size: u8) {
         self.init_bits(index * v_size as usize, v, v_size)
     }
 
+    /// Initializes `index`-th fragment of `v_size` bits to `v` and returns `true` if all its bits are cleared.
+    /// Otherwise, leaves the fragment unchanged and returns `false`.
+    /// So, unlike [`Self::init_fragment`], it enforces that each fragment is written at most once
+    /// (note that writing `0` leaves the fragment cleared, so it can be written again).
+    /// Panics if the range is out of bounds.
+    #[inline] fn try_init_fragment(&mut self, index: usize, v: u64, v_size: u8) -> bool {
+        if self.get_fragment(index, v_size) != 0 { return false; }
+        self.init_fragment(index, v, v_size);
+        true
+    }
+
     /// Initializes `index`-th fragment of `v_size` bits, i.e. bits with indices in range [`index*v_size`, `index*v_size+v_size`), to `v`
     /// Next, increases `index` by 1. Panics if the range is out of bounds.
     /// Before initialization, the bits are assumed to be cleared or already set to `v`.
@@ -389,12 +726,25 @@ pub trait BitAccess {
 
     /// Swaps ranges of bits: [`index1*v_size`, `index1*v_size+v_size`) with [`index2*v_size`, `index2*v_size+v_size`).
     fn swap_fragments(&mut self, index1: usize, index2: usize, v_size: u8) {
-        // TODO faster implementation
         let v1 = self.get_fragment(index1, v_size);
         unsafe{self.set_fragment_unchecked(index1, self.get_fragment(index2, v_size), v_size)};
         unsafe{self.set_fragment_unchecked(index2, v1, v_size);}
     }
 
+    /// Swaps fragments `index1` and `index2` of `v_size` bits each (see [`Self::swap_fragments`]),
+    /// but only if `predicate` called with their values returns `true`. Returns whether the fragments were swapped.
+    fn conditionally_swap_fragments<P>(&mut self, index1: usize, index2: usize, v_size: u8, predicate: P) -> bool
+        where P: FnOnce(u64, u64) -> bool
+    {
+        let (v1, v2) = (self.get_fragment(index1, v_size), self.get_fragment(index2, v_size));
+        if !predicate(v1, v2) { return false; }
+        unsafe {
+            self.set_fragment_unchecked(index1, v2, v_size);
+            self.set_fragment_unchecked(index2, v1, v_size);
+        }
+        true
+    }
+
     /// Conditionally (if `new_value` does not return [`None`]) changes
     /// the value `old` stored at bits `[begin, begin+v_size)`
     /// to the one returned by `new_value` (whose argument is `old`).
@@ -439,6 +789,58 @@ pub trait BitAccess {
         self.conditionally_copy_bits(src, predicate, index * v_size as usize, v_size)
     }
 
+    /// Rotates the bits of the fragment `index` of `v_size` bits left by `by % v_size` bits.
+    /// The bits shifted out of the most significant end of the fragment reappear at its least significant end.
+    /// `v_size` must be in range [0, 63]; nothing is changed if it is 0.
+    fn rotate_fragment_left(&mut self, index: usize, v_size: u8, by: u8) {
+        if v_size == 0 { return; }
+        let by = by % v_size;
+        if by == 0 { return; }
+        self.conditionally_change_fragment(|v| Some(((v << by) | (v >> (v_size - by))) & n_lowest_bits(v_size)), index, v_size);
+    }
+
+    /// Replaces each of the first `count` fragments of `v_size` bits of `self`
+    /// with the larger of it and the corresponding fragment of `other`.
+    /// Panics if the fragments are out of bounds.
+    fn fragmentwise_max(&mut self, other: &[u64], v_size: u8, count: usize) {
+        for index in 0..count {
+            let o = other.get_fragment(index, v_size);
+            self.conditionally_change_fragment(|s| (s < o).then_some(o), index, v_size);
+        }
+    }
+
+    /// Replaces each of the first `count` fragments of `v_size` bits of `self`
+    /// with the smaller of it and the corresponding fragment of `other`.
+    /// Panics if the fragments are out of bounds.
+    fn fragmentwise_min(&mut self, other: &[u64], v_size: u8, count: usize) {
+        for index in 0..count {
+            let o = other.get_fragment(index, v_size);
+            self.conditionally_change_fragment(|s| (o < s).then_some(o), index, v_size);
+        }
+    }
+
+    /// Binary searches the first `count` fragments of `v_size` bits, which must be sorted in non-descending order,
+    /// for the given `value`.
+    /// Returns `Ok` with index of a fragment equal to `value` (if there are many such fragments, any of them can be returned),
+    /// or `Err` with index where `value` could be inserted maintaining the order (like [`slice::binary_search`]).
+    /// Panics if the fragments are out of bounds.
+    fn binary_search_fragment(&self, value: u64, v_size: u8, count: usize) -> Result<usize, usize> {
+        let (mut begin, mut end) = (0, count);
+        while begin < end {
+            let mid = begin + (end - begin) / 2;
+            let v = self.get_fragment(mid, v_size);
+            if v < value { begin = mid + 1 }
+            else if v > value { end = mid }
+            else { return Ok(mid) }
+        }
+        Err(begin)
+    }
+
+    /// Returns the number of fragments equal to `value` among the first `count` fragments of `v_size` bits.
+    /// If `v_size` divides 64, whole segments are compared at once (in a SWAR manner).
+    /// Panics if the fragments are out of bounds.
+    fn count_fragments_eq(&self, value: u64, v_size: u8, count: usize) -> usize;
+
     /// Returns the number of trailing 0 bits.
     fn trailing_zero_bits(&self) -> usize;
 
@@ -450,6 +852,10 @@ pub trait BitAccess {
     /// Retruns [`None`] if there is no such index.
     fn find_bit_one(&self, start_index: usize) -> Option<usize>;
 
+    /// Returns the lowest index of 0-bit that is grater or equal to `start_index`.
+    /// Retruns [`None`] if there is no such index.
+    fn find_bit_zero(&self, start_index: usize) -> Option<usize>;
+
     /// Returns the greatest index of 1-bit that is lower or equal to `start_index`.
     /// The result is undefined if there is no such index.
     unsafe fn rfind_bit_one_unchecked(&self, start_index: usize) -> usize;
@@ -460,6 +866,32 @@ pub trait BitVec where Self: Sized {
     /// Returns vector of `segments_len` 64 bit segments, each segment initialized to `segments_value`.
     fn with_64bit_segments(segments_value: u64, segments_len: usize) -> Self;
 
+    /// Returns vector of `segments_len` 64 bit segments, each segment initialized to `segments_value`,
+    /// or an error if the memory cannot be allocated (instead of aborting like [`Self::with_64bit_segments`]).
+    fn try_with_64bit_segments(segments_value: u64, segments_len: usize) -> Result<Self, TryReserveError>;
+
+    /// Returns vector of `segments_len` 64 bit segments, with all bits set to `0`,
+    /// or an error if the memory cannot be allocated.
+    #[inline(always)] fn try_with_zeroed_64bit_segments(segments_len: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_64bit_segments(0, segments_len)
+    }
+
+    /// Returns vector of `segments_len` 64 bit segments, with all bits set to `1`,
+    /// or an error if the memory cannot be allocated.
+    #[inline(always)] fn try_with_filled_64bit_segments(segments_len: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_64bit_segments(u64::MAX, segments_len)
+    }
+
+    /// Returns vector of `bit_len` bits, all set to `0`, or an error if the memory cannot be allocated.
+    #[inline(always)] fn try_with_zeroed_bits(bit_len: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_zeroed_64bit_segments(ceiling_div(bit_len, 64))
+    }
+
+    /// Returns vector of `bit_len` bits, all set to `1`, or an error if the memory cannot be allocated.
+    #[inline(always)] fn try_with_filled_bits(bit_len: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_filled_64bit_segments(ceiling_div(bit_len, 64))
+    }
+
     /// Returns vector of bits filled with `words_count` `word`s of length `word_len_bits` bits each.
     fn with_bitwords(word: u64, word_len_bits: u8, words_count: usize) -> Self;
 
@@ -483,7 +915,76 @@ pub trait BitVec where Self: Sized {
         Self::with_filled_64bit_segments(ceiling_div(bit_len, 64))
     }
 
-    //fn with_bit_fragments<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragment_count: usize, bits_per_fragment: u8) -> Box<[u64]>
+    /// Returns vector of `fragment_count` fragments of `bits_per_fragment` bits each,
+    /// initialized to the consecutive `items` (remaining fragments are set to `0`).
+    /// Panics if `items` has more than `fragment_count` elements.
+    fn with_bit_fragments<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragment_count: usize, bits_per_fragment: u8) -> Self;
+
+    /// Returns vector of `fragment_count` fragments of `bits_per_fragment` bits each,
+    /// in which the fragments with indices given in `pairs` are initialized to the paired values
+    /// and all remaining fragments are set to `0`.
+    /// Each index must be less than `fragment_count` (otherwise the method panics) and can occur only once.
+    /// Each value must fit in `bits_per_fragment` bits.
+    fn with_sparse_fragments<I: IntoIterator<Item=(usize, u64)>>(pairs: I, fragment_count: usize, bits_per_fragment: u8) -> Self;
+
+    /// Returns vector of fragments of `bits_per_fragment` bits each, initialized to the consecutive `items`.
+    /// The number of fragments is taken from the length of `items` iterator.
+    #[inline] fn with_bit_fragments_exact<V: Into<u64>, I>(items: I, bits_per_fragment: u8) -> Self
+        where I: IntoIterator<Item=V>, I::IntoIter: ExactSizeIterator
+    {
+        let items = items.into_iter();
+        let fragment_count = items.len();
+        Self::with_bit_fragments(items, fragment_count, bits_per_fragment)
+    }
+
+    /// Returns vector of consecutive `words`, each of `word_len_bits` bits.
+    /// The `i`-th word can be read by [`BitAccess::get_fragment`] with index `i`.
+    /// Each word must fit in `word_len_bits` bits, which is checked only in debug builds.
+    #[inline] fn with_bitword_slice(words: &[u64], word_len_bits: u8) -> Self {
+        debug_assert!(words.iter().all(|w| *w <= n_lowest_bits_0_64(word_len_bits)),
+            "with_bitword_slice: some words do not fit in {word_len_bits} bits");
+        Self::with_bit_fragments_exact(words.iter().copied(), word_len_bits)
+    }
+
+    /// Returns vector of consecutive `items`, each stored in `v_size` lowest bits of a slot that begins at a byte boundary.
+    /// The width of the slot is `v_size` rounded up to a multiple of 8,
+    /// so up to 7 bits per item are wasted in exchange for byte-aligned access.
+    /// The items can be read by [`BitAccess::get_fragment_aligned`].
+    #[inline] fn with_byte_aligned_fragments<V: Into<u64>, I>(items: I, v_size: u8) -> Self
+        where I: IntoIterator<Item=V>, I::IntoIter: ExactSizeIterator
+    {
+        let mask = n_lowest_bits_0_64(v_size);
+        Self::with_bit_fragments_exact(items.into_iter().map(|v| v.into() & mask), byte_aligned_width(v_size))
+    }
+
+    /// Returns vector of consecutive `bits` (`1` for each `true` and `0` for each `false`)
+    /// and the number of bits, i.e. the number of items consumed from `bits`.
+    /// The `i`-th item is stored as the bit with index `i` (see [`BitAccess::get_bit`]),
+    /// and the remaining bits of the last segment are set to `0`.
+    fn from_bits<I: IntoIterator<Item=bool>>(bits: I) -> (Self, usize);
+
+    /// Consumes `self` and returns iterator that yields (by value) its first `count` fragments of `v_size` bits each,
+    /// i.e. the same fragments as [`BitAccess::fragments`].
+    /// Panics if the fragments are out of bounds.
+    #[inline] fn into_fragments(self, v_size: u8, count: usize) -> IntoFragmentsIterator<Self> where Self: Deref<Target = [u64]> {
+        IntoFragmentsIterator::new(self, v_size, count)
+    }
+}
+
+/// Returns `v_size` rounded up to a multiple of 8.
+#[inline(always)] const fn byte_aligned_width(v_size: u8) -> u8 { (v_size + 7) & !7 }
+
+/// Packs consecutive `bits` into 64-bit segments, least significant bit first.
+/// Returns the segments and the number of bits.
+fn pack_bits<I: IntoIterator<Item=bool>>(bits: I) -> (Vec<u64>, usize) {
+    let mut segments = Vec::new();
+    let mut len = 0;
+    for bit in bits {
+        if len % 64 == 0 { segments.push(0); }
+        if bit { *segments.last_mut().unwrap() |= 1 << (len % 64); }
+        len += 1;
+    }
+    (segments, len)
 }
 
 impl BitVec for Box<[u64]> {
@@ -491,11 +992,41 @@ impl BitVec for Box<[u64]> {
         vec![segments_value; segments_len].into_boxed_slice()
     }
 
+    fn try_with_64bit_segments(segments_value: u64, segments_len: usize) -> Result<Self, TryReserveError> {
+        let mut result = Vec::new();
+        result.try_reserve_exact(segments_len)?;
+        result.resize(segments_len, segments_value);
+        Ok(result.into_boxed_slice())
+    }
+
     fn with_bitwords(word: u64, word_len_bits: u8, words_count: usize) -> Self {
         let mut result = Self::with_zeroed_bits(words_count * word_len_bits as usize);
         for index in 0..words_count { result.init_fragment(index, word, word_len_bits); }
         result
     }
+
+    fn with_bit_fragments<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragment_count: usize, bits_per_fragment: u8) -> Self {
+        let mut result = Self::with_zeroed_bits(fragment_count * bits_per_fragment as usize);
+        for (index, v) in items.into_iter().enumerate() {
+            assert!(index < fragment_count, "with_bit_fragments: more than {fragment_count} items");
+            result.init_fragment(index, v.into(), bits_per_fragment);
+        }
+        result
+    }
+
+    fn with_sparse_fragments<I: IntoIterator<Item=(usize, u64)>>(pairs: I, fragment_count: usize, bits_per_fragment: u8) -> Self {
+        let mut result = Self::with_zeroed_bits(fragment_count * bits_per_fragment as usize);
+        for (index, v) in pairs {
+            assert!(index < fragment_count, "with_sparse_fragments: index {index} out of {fragment_count} fragments");
+            result.init_fragment(index, v, bits_per_fragment);
+        }
+        result
+    }
+
+    fn from_bits<I: IntoIterator<Item=bool>>(bits: I) -> (Self, usize) {
+        let (segments, len) = pack_bits(bits);
+        (segments.into_boxed_slice(), len)
+    }
 }
 
 #[cfg(feature = "aligned-vec")]
@@ -504,11 +1035,52 @@ impl<const ALIGN: usize> BitVec for aligned_vec::ABox<[u64], aligned_vec::ConstA
         aligned_vec::avec![[ALIGN] | segments_value; segments_len].into_boxed_slice()
     }
 
+    fn try_with_64bit_segments(segments_value: u64, segments_len: usize) -> Result<Self, TryReserveError> {
+        if segments_len == 0 { return Ok(Self::with_64bit_segments(segments_value, 0)); }
+        // aligned_vec does not expose fallible allocation, so the memory is allocated directly;
+        // TryReserveError cannot be constructed, so all failures are reported as the error of reserving usize::MAX segments
+        let capacity_error = || Vec::<u64>::new().try_reserve_exact(usize::MAX).unwrap_err();
+        let layout = segments_len.checked_mul(8)
+            .and_then(|size| std::alloc::Layout::from_size_align(size, ALIGN.max(std::mem::align_of::<u64>())).ok())
+            .ok_or_else(capacity_error)?;
+        let ptr = unsafe { std::alloc::alloc(layout) } as *mut u64;
+        if ptr.is_null() { return Err(capacity_error()); }
+        unsafe {
+            for i in 0..segments_len { ptr.add(i).write(segments_value); }
+            Ok(aligned_vec::ABox::from_raw_parts(layout.align(), std::ptr::slice_from_raw_parts_mut(ptr, segments_len)))
+        }
+    }
+
     fn with_bitwords(word: u64, word_len_bits: u8, words_count: usize) -> Self {
         let mut result = Self::with_zeroed_bits(words_count * word_len_bits as usize);
         for index in 0..words_count { result.init_fragment(index, word, word_len_bits); }
         result
     }
+
+    fn with_bit_fragments<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragment_count: usize, bits_per_fragment: u8) -> Self {
+        let mut result = Self::with_zeroed_bits(fragment_count * bits_per_fragment as usize);
+        for (index, v) in items.into_iter().enumerate() {
+            assert!(index < fragment_count, "with_bit_fragments: more than {fragment_count} items");
+            result.init_fragment(index, v.into(), bits_per_fragment);
+        }
+        result
+    }
+
+    fn with_sparse_fragments<I: IntoIterator<Item=(usize, u64)>>(pairs: I, fragment_count: usize, bits_per_fragment: u8) -> Self {
+        let mut result = Self::with_zeroed_bits(fragment_count * bits_per_fragment as usize);
+        for (index, v) in pairs {
+            assert!(index < fragment_count, "with_sparse_fragments: index {index} out of {fragment_count} fragments");
+            result.init_fragment(index, v, bits_per_fragment);
+        }
+        result
+    }
+
+    fn from_bits<I: IntoIterator<Item=bool>>(bits: I) -> (Self, usize) {
+        let (segments, len) = pack_bits(bits);
+        let mut result = Self::with_zeroed_64bit_segments(segments.len());
+        result.copy_from_slice(&segments);
+        (result, len)
+    }
 }
 
 /*#[inline(always)] pub fn bitvec_len_for_bits(bits_len: usize) -> usize { ceiling_div(bits_len, 64) }
@@ -527,15 +1099,46 @@ impl<const ALIGN: usize> BitVec for aligned_vec::ABox<[u64], aligned_vec::ConstA
 }
 #[inline(always)] pub fn bitvec_with_bits_len_filled(bits_len: usize) -> Box<[u64]> {
     bitvec_with_segments_len_filled(bitvec_len_for_bits(bits_len))
+}*/
+
+/// Returns the number of positions at which bits of `a` and `b` differ (i.e. the number of ones in `a ^ b`)
+/// and iterator over these positions, in increasing order.
+/// Panics if `a` and `b` have different lengths.
+pub fn symmetric_diff<'a>(a: &'a [u64], b: &'a [u64]) -> (usize, impl Iterator<Item = usize> + 'a) {
+    assert_eq!(a.len(), b.len(), "symmetric_diff: bit vectors of different lengths");
+    let count = a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones() as usize).sum();
+    let positions = a.iter().zip(b).enumerate().flat_map(|(segment_index, (a, b))| {
+        let mut diff = a ^ b;
+        std::iter::from_fn(move || {
+            if diff == 0 { return None; }
+            let bit = diff.trailing_zeros() as usize;
+            diff &= diff - 1;
+            Some(segment_index * 64 + bit)
+        })
+    });
+    (count, positions)
 }
 
-pub fn bitvec_with_items<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragment_count: usize, bits_per_fragment: u8) -> Box<[u64]> {
-    let mut result = bitvec_with_bits_len_zeroed(fragment_count * bits_per_fragment as usize);
-    for (i, v) in items.into_iter().enumerate() {
-        result.init_fragment(i, v.into(), bits_per_fragment);
+/// Returns vector of `a_len + b_len` fragments of `v_size` bits each, sorted in non-decreasing order,
+/// that contains (with duplicates) the first `a_len` fragments of `a` and the first `b_len` fragments of `b`.
+/// Both `a` and `b` must be sorted in non-decreasing order.
+pub fn merge_sorted_fragments(a: &[u64], a_len: usize, b: &[u64], b_len: usize, v_size: u8) -> Box<[u64]> {
+    let mut result = Box::<[u64]>::with_zeroed_bits((a_len + b_len) * v_size as usize);
+    let (mut a_index, mut b_index) = (0, 0);
+    while a_index < a_len && b_index < b_len {
+        let (a_value, b_value) = (a.get_fragment(a_index, v_size), b.get_fragment(b_index, v_size));
+        if a_value <= b_value {
+            result.init_fragment(a_index + b_index, a_value, v_size);
+            a_index += 1;
+        } else {
+            result.init_fragment(a_index + b_index, b_value, v_size);
+            b_index += 1;
+        }
     }
+    for a_index in a_index..a_len { result.init_fragment(a_index + b_index, a.get_fragment(a_index, v_size), v_size); }
+    for b_index in b_index..b_len { result.init_fragment(a_len + b_index, b.get_fragment(b_index, v_size), v_size); }
     result
-}*/
+}
 
 /// Set `bit_nr` bit of `v` to given `value`.
 #[inline(always)] fn set_bit_to(to_change: &mut u64, bit_nr: usize, value: bool) {
@@ -548,7 +1151,7 @@ pub fn bitvec_with_items<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragme
     //*to_change = (*to_change & !m) | ((value as u64).wrapping_neg() & m);
 }
 
-#[inline(always)] fn set_bits_to(to_change: &mut u64, shifted_v: u64, shifted_v_mask: u64) {
+#[inline(always)] fn set_masked_bits(to_change: &mut u64, shifted_v: u64, shifted_v_mask: u64) {
     *to_change &= !shifted_v_mask;
     *to_change |= shifted_v;
 }
@@ -606,21 +1209,103 @@ impl BitAccess for [u64] {
     }
 
     fn count_bit_zeros(&self) -> usize {
-        self.into_iter().map(|s| s.count_zeros() as usize).sum()
+        self.len() * 64 - self.count_bit_ones()
+    }
+
+    #[inline] fn count_bit_ones(&self) -> usize {
+        crate::popcount::count_ones(self)
+    }
+
+    fn count_ones_in_range(&self, begin: usize, end: usize) -> usize {
+        assert!(begin <= end, "count_ones_in_range: begin ({begin}) must not exceed end ({end})");
+        let (begin_segment, end_segment) = (begin / 64, end / 64);
+        if begin_segment == end_segment {
+            return if begin == end { 0 } else {
+                ((self[begin_segment] >> (begin % 64)) & n_lowest_bits((end - begin) as u8)).count_ones() as usize
+            }
+        }
+        let mut result = (self[begin_segment] >> (begin % 64)).count_ones() as usize;
+        result += self[begin_segment+1..end_segment].iter().map(|s| s.count_ones() as usize).sum::<usize>();
+        if !end.is_multiple_of(64) { result += (self[end_segment] & n_lowest_bits((end % 64) as u8)).count_ones() as usize; }
+        result
     }
 
-    fn count_bit_ones(&self) -> usize {
-        self.into_iter().map(|s| s.count_ones() as usize).sum()
+    fn select_ones(&self, mut rank: usize) -> Option<usize> {
+        for (segment_index, segment) in self.iter().enumerate() {
+            let ones = segment.count_ones() as usize;
+            if rank < ones { return Some(segment_index * 64 + select64(*segment, rank as u8) as usize); }
+            rank -= ones;
+        }
+        None
+    }
+
+    fn split_count_ones(&self, mid: usize, end: usize) -> (usize, usize) {
+        assert!(mid <= end, "split_count_ones: mid ({mid}) must not exceed end ({end})");
+        let (mid_segment, end_segment) = (mid / 64, end / 64);
+        let mut first: usize = self[..mid_segment].iter().map(|s| s.count_ones() as usize).sum();
+        let mut second: usize = self[mid_segment..end_segment].iter().map(|s| s.count_ones() as usize).sum();
+        if !end.is_multiple_of(64) { second += (self[end_segment] & n_lowest_bits((end % 64) as u8)).count_ones() as usize; }
+        if !mid.is_multiple_of(64) {  // move ones of [64*mid_segment, mid) from second to first
+            let ones = (self[mid_segment] & n_lowest_bits((mid % 64) as u8)).count_ones() as usize;
+            first += ones;
+            second -= ones;
+        }
+        (first, second)
+    }
+
+    fn is_disjoint(&self, other: &[u64]) -> bool {
+        debug_assert_eq!(self.len(), other.len(), "is_disjoint: bit vectors of different lengths");
+        self.iter().zip(other).all(|(a, b)| a & b == 0)
+    }
+
+    fn hamming_distance(&self, other: &[u64]) -> usize {
+        assert_eq!(self.len(), other.len(), "hamming_distance: bit vectors of different lengths");
+        self.iter().zip(other).map(|(a, b)| (a ^ b).count_ones() as usize).sum()
+    }
+
+    fn equals_bits(&self, other: &[u64], bit_len: usize) -> bool {
+        let (full, tail) = (bit_len / 64, (bit_len % 64) as u8);
+        self[..full] == other[..full] &&
+            (tail == 0 || (self[full] ^ other[full]) & n_lowest_bits(tail) == 0)
     }
 
     #[inline(always)] fn bit_ones(&'_ self) -> BitOnesIterator<'_> {
         BitOnesIterator::new(self)
     }
 
+    #[inline] fn bit_ones_in_segments(&'_ self, seg_begin: usize, seg_end: usize) -> BitOnesIterator<'_> {
+        BitOnesIterator::with_segment_offset(&self[seg_begin..seg_end], seg_begin)
+    }
+
+    #[inline(always)] fn bit_one_runs(&'_ self) -> BitRunsIterator<'_> {
+        BitRunsIterator::new(self)
+    }
+
+    #[inline] fn get_byte_fragment(&self, index: usize, bytes: u8) -> u64 {
+        assert!((1..=8).contains(&bytes), "get_byte_fragment: bytes must be in range [1, 8]");
+        #[cfg(target_endian = "little")] {
+            let storage = unsafe { std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len() * 8) };
+            let begin = index * bytes as usize;
+            if let Some(window) = storage.get(begin..begin + 8) {   // fast path: read whole 8 bytes and mask
+                return u64::from_le_bytes(window.try_into().unwrap()) & n_lowest_bits_1_64(bytes * 8);
+            }
+            let mut result = [0u8; 8];
+            result[..bytes as usize].copy_from_slice(&storage[begin..begin + bytes as usize]);
+            u64::from_le_bytes(result)
+        }
+        #[cfg(not(target_endian = "little"))] {
+            self.get_fragment(index, bytes * 8)
+        }
+    }
+
     #[inline(always)] fn bit_zeros(&'_ self) -> BitZerosIterator<'_> {
         BitZerosIterator::new(self)
     }
 
+    #[inline(always)] fn fragments(&'_ self, v_size: u8, count: usize) -> FragmentsIterator<'_> {
+        FragmentsIterator::new(self, v_size, count)
+    }
+
     #[inline(always)] fn bit_iter(&'_ self) -> BitIterator<'_> {
         BitIterator::new(self)
     }
@@ -633,6 +1318,12 @@ impl BitAccess for [u64] {
         BitIterator::with_range_unchecked(self, bit_range)
     }
 
+    #[inline] fn peek_bits(&self, begin: usize, max_len: u8) -> u64 {
+        let available = (self.len() * 64).saturating_sub(begin);
+        let len = (max_len as usize).min(available) as u8;
+        if len == 0 { 0 } else { self.get_bits(begin, len) }
+    }
+
     #[inline] fn try_get_bits_unmasked(&self, begin: usize, len: u8) -> Option<u64> {
         //((begin+(len as usize))/64 < self.len()).then(|| unsafe{self.get_bits_unmasked_unchecked(begin, len)})
         let (segment, offset) = (begin / 64, (begin % 64) as u8);
@@ -679,36 +1370,89 @@ impl BitAccess for [u64] {
 
     fn set_bits(&mut self, begin: usize, v: u64, len: u8) {
         let (segment, offset) = (begin / 64, (begin % 64) as u8);
-        let v_mask = n_lowest_bits(len);
+        let v_mask = n_lowest_bits_0_64(len);
         //let lo_bit_len = 64-offset;
         if offset + len > 64 /*len > lo_bit_len*/ {
             let shift = 64-offset; //lo_bit_len
-            set_bits_to(&mut self[segment+1], v>>shift, v_mask>>shift);
+            set_masked_bits(&mut self[segment+1], v>>shift, v_mask>>shift);
         }
-        set_bits_to(&mut self[segment], v<<offset, v_mask<<offset);
+        set_masked_bits(&mut self[segment], v<<offset, v_mask<<offset);
+    }
+
+    #[inline] fn try_set_bits(&mut self, begin: usize, v: u64, len: u8) -> Option<()> {
+        (begin.checked_add(len as usize)? <= self.len() * 64).then(|| unsafe { self.set_bits_unchecked(begin, v, len) })
     }
 
     unsafe fn set_bits_unchecked(&mut self, begin: usize, v: u64, len: u8) {
         let (segment, offset) = (begin / 64, (begin % 64) as u8);
-        let v_mask = n_lowest_bits(len);
+        let v_mask = n_lowest_bits_0_64(len);
         if offset + len > 64 {
             let shift = 64-offset; //lo_bit_len
             debug_assert!(segment+1 < self.len());
-            set_bits_to(self.get_unchecked_mut(segment+1), v>>shift, v_mask>>shift);
+            set_masked_bits(self.get_unchecked_mut(segment+1), v>>shift, v_mask>>shift);
         }
         debug_assert!(segment < self.len());
-        set_bits_to(self.get_unchecked_mut(segment), v<<offset, v_mask<<offset);
+        set_masked_bits(self.get_unchecked_mut(segment), v<<offset, v_mask<<offset);
+    }
+
+    fn set_bits_to(&mut self, begin: usize, end: usize, value: bool) {
+        assert!(begin <= end, "set_bits_to: begin ({begin}) must not exceed end ({end})");
+        if begin == end { return; }
+        let (begin_segment, end_segment) = (begin / 64, (end - 1) / 64);
+        let fill = if value { u64::MAX } else { 0 };
+        let head_mask = u64::MAX << (begin % 64);
+        let tail_mask = u64::MAX >> (63 - (end - 1) % 64);
+        if begin_segment == end_segment {
+            let mask = head_mask & tail_mask;
+            set_masked_bits(&mut self[begin_segment], fill & mask, mask);
+        } else {
+            set_masked_bits(&mut self[begin_segment], fill & head_mask, head_mask);
+            self[begin_segment+1..end_segment].fill(fill);
+            set_masked_bits(&mut self[end_segment], fill & tail_mask, tail_mask);
+        }
     }
 
     fn xor_bits(&mut self, begin: usize, v: u64, len: u8) {
         let (segment, offset) = (begin / 64, (begin % 64) as u8);
-        if offset + len > 64 {
+        if offset + len > 64 {  // so offset > 0 and the shift below is less than 64, also for len == 64
             let shift = 64-offset;
             self[segment+1] ^= v >> shift;
         }
         self[segment] ^= v << offset;
     }
 
+    fn and_assign(&mut self, other: &[u64]) {
+        debug_assert_eq!(self.len(), other.len());
+        for (s, o) in self.iter_mut().zip(other) { *s &= o; }
+    }
+
+    fn or_assign(&mut self, other: &[u64]) {
+        debug_assert_eq!(self.len(), other.len());
+        for (s, o) in self.iter_mut().zip(other) { *s |= o; }
+    }
+
+    fn xor_assign(&mut self, other: &[u64]) {
+        debug_assert_eq!(self.len(), other.len());
+        for (s, o) in self.iter_mut().zip(other) { *s ^= o; }
+    }
+
+    fn swap_fragments(&mut self, index1: usize, index2: usize, v_size: u8) {
+        let (begin1, begin2) = (index1 * v_size as usize, index2 * v_size as usize);
+        let mask = n_lowest_bits_0_64(v_size);
+        let (segment, offset1) = (begin1 / 64, (begin1 % 64) as u32);
+        let offset2 = (begin2 % 64) as u32;
+        if segment == begin2 / 64 && offset1 + v_size as u32 <= 64 && offset2 + v_size as u32 <= 64 {
+            // both fragments are in the same segment, xor-swap them in-place
+            let w = self[segment];
+            let diff = ((w >> offset1) ^ (w >> offset2)) & mask;
+            self[segment] = w ^ (diff << offset1) ^ (diff << offset2);
+        } else {
+            let diff = self.get_bits(begin1, v_size) ^ self.get_bits(begin2, v_size);
+            self.xor_bits(begin1, diff, v_size);
+            self.xor_bits(begin2, diff, v_size);
+        }
+    }
+
     fn conditionally_change_bits<NewValue>(&mut self, new_value: NewValue, begin: usize, v_size: u8) -> u64
         where NewValue: FnOnce(u64) -> Option<u64>
     {
@@ -723,9 +1467,9 @@ impl BitAccess for [u64] {
         } & v_mask;
         if let Some(v) = new_value(r) {
             if v_size as u64 > bits_in_w1 {
-                set_bits_to(&mut self[segment + 1], v >> bits_in_w1, v_mask >> bits_in_w1);
+                set_masked_bits(&mut self[segment + 1], v >> bits_in_w1, v_mask >> bits_in_w1);
             }
-            set_bits_to(&mut self[segment], v << offset, v_mask << offset);
+            set_masked_bits(&mut self[segment], v << offset, v_mask << offset);
         }
         r
     }
@@ -743,17 +1487,39 @@ impl BitAccess for [u64] {
             let self_bits = self_w1 | ((self[segment+1] & w2_mask) << bits_in_w1);
             let src_w2 = src[segment+1] & w2_mask;
             if predicate(self_bits, src_w1 | (src_w2 << bits_in_w1)) {
-                set_bits_to(&mut self[segment+1], src_w2, w2_mask);
-                set_bits_to(&mut self[segment], src_w1 << offset, v_mask << offset);
+                set_masked_bits(&mut self[segment+1], src_w2, w2_mask);
+                set_masked_bits(&mut self[segment], src_w1 << offset, v_mask << offset);
             }
         } else {
             src_w1 &= v_mask;
             if predicate(self_w1 & v_mask, src_w1) {
-                set_bits_to(&mut self[segment], src_w1 << offset, v_mask << offset);
+                set_masked_bits(&mut self[segment], src_w1 << offset, v_mask << offset);
             }
         };
     }
 
+    fn count_fragments_eq(&self, value: u64, v_size: u8, count: usize) -> usize {
+        let mask = n_lowest_bits_0_64(v_size);
+        if value > mask { return 0; }
+        if v_size == 0 || 64 % v_size != 0 {
+            return (0..count).filter(|i| self.get_fragment(*i, v_size) == value).count();
+        }
+        let per_segment = 64 / v_size as usize;
+        let full_segments = count / per_segment;
+        let value_splat = u64::MAX / mask * value;
+        let low_bits = u64::MAX / mask * n_lowest_bits(v_size - 1);   // all bits but the highest of each fragment
+        let mut result: usize = self[..full_segments].iter().map(|segment| {
+            let diff = segment ^ value_splat;   // fragments equal to value are zeroed
+            // the highest bit of a fragment is set in zeros iff the fragment of diff is 0:
+            let zeros = !(((diff & low_bits) + low_bits) | diff | low_bits);
+            zeros.count_ones() as usize
+        }).sum();
+        for i in full_segments * per_segment..count {
+            if self.get_fragment(i, v_size) == value { result += 1; }
+        }
+        result
+    }
+
     fn trailing_zero_bits(&self) -> usize {
         for (i, v) in self.iter().copied().enumerate() {
             if v != 0 { return i * 64 + v.trailing_zeros() as usize; }
@@ -771,6 +1537,16 @@ impl BitAccess for [u64] {
         Some(word_index * 64 + (bits.trailing_zeros() as usize))
     }
 
+    fn find_bit_zero(&self, start_index: usize) -> Option<usize> {
+        let mut word_index = start_index / 64;
+        let mut bits = !self.get(word_index)? & !n_lowest_bits((start_index % 64) as u8);
+        while bits == 0 {
+            word_index += 1;
+            bits = !*self.get(word_index)?;
+        }
+        Some(word_index * 64 + (bits.trailing_zeros() as usize))
+    }
+
     unsafe fn find_bit_one_unchecked(&self, start_index: usize) -> usize {
         let mut word_index = start_index / 64;
         debug_assert!(word_index < self.len());
@@ -826,6 +1602,80 @@ mod tests {
         assert_eq!(b.get_fragment(1, 30), 0);
     }
 
+    #[test]
+    fn swap_many_fragments() {
+        let old_swap = |b: &mut [u64], index1: usize, index2: usize, v_size: u8| {
+            let v1 = b.get_fragment(index1, v_size);
+            b.set_fragment(index1, b.get_fragment(index2, v_size), v_size);
+            b.set_fragment(index2, v1, v_size);
+        };
+        for v_size in [1u8, 3, 7, 8, 13, 30, 32, 63, 64] {
+            let count = 300;
+            let mut b = Box::<[u64]>::with_bit_fragments_exact((0..count).map(|i| (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) & n_lowest_bits_0_64(v_size)), v_size);
+            let mut expected = b.clone();
+            for i in 0..count - 1 {
+                b.swap_fragments(i, i + 1, v_size);
+                old_swap(&mut expected, i, i + 1, v_size);
+                let j = (i * 7) % count;
+                b.swap_fragments(i, j, v_size);
+                old_swap(&mut expected, i, j, v_size);
+            }
+            assert_eq!(b, expected, "v_size={v_size}");
+        }
+    }
+
+    #[test]
+    fn bubble_sort_fragments() {
+        let mut b = Box::<[u64]>::with_bit_fragments_exact([200u8, 7, 255, 0], 8);
+        let mut swaps = 0;
+        for pass in 0..3 {
+            for i in 0..3 - pass {
+                if b.conditionally_swap_fragments(i, i + 1, 8, |v1, v2| v1 > v2) { swaps += 1; }
+            }
+        }
+        assert_eq!(swaps, 4);
+        assert_eq!(b, Box::<[u64]>::with_bit_fragments_exact([0u8, 7, 200, 255], 8));
+        assert!(!b.conditionally_swap_fragments(0, 3, 8, |v1, v2| v1 > v2));
+        assert!(b.conditionally_swap_fragments(0, 3, 8, |v1, v2| v1 < v2));
+        assert_eq!(b, Box::<[u64]>::with_bit_fragments_exact([255u8, 7, 200, 0], 8));
+    }
+
+    #[test]
+    fn fragments_from_iter() {
+        let items = [5u8, 0, 31, 17, 9, 22, 30, 1, 13, 27, 4, 19, 8];
+        let mut expected = Box::<[u64]>::with_zeroed_bits(15 * 5);
+        for (i, v) in items.iter().enumerate() { expected.set_fragment(i, *v as u64, 5); }
+        let b = Box::<[u64]>::with_bit_fragments(items, 15, 5);
+        assert_eq!(b, expected);
+        assert_eq!(b.get_fragment(13, 5), 0);
+        assert_eq!(b.get_fragment(14, 5), 0);
+        let b = Box::<[u64]>::with_bit_fragments_exact(items, 5);
+        assert_eq!(b.len(), 2);
+        assert_eq!(b, expected);
+        assert!(Box::<[u64]>::with_bit_fragments_exact(Vec::<u64>::new(), 5).is_empty());
+    }
+
+    #[test]
+    fn fragments_byte_aligned() {
+        let items = [5u8, 0, 31, 17, 9, 22, 30, 1, 13, 27, 4, 19, 8];
+        let b = Box::<[u64]>::with_byte_aligned_fragments(items, 5);
+        assert_eq!(b.len(), 2);
+        for (i, v) in items.iter().enumerate() {
+            assert_eq!(b.get_fragment_aligned(i, 5), *v as u64);
+            assert_eq!(b.get_bits(i * 8, 8), *v as u64);    // begins at byte boundary, unused bits are zeroed
+        }
+        let b = Box::<[u64]>::with_byte_aligned_fragments([0x1FFu16, 0x1234], 9);
+        assert_eq!(b.get_fragment_aligned(0, 9), 0x1FF);
+        assert_eq!(b.get_fragment_aligned(1, 9), 0x034);
+        assert_eq!(b.get_bits(16, 16), 0x034);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fragments_from_too_long_iter() {
+        Box::<[u64]>::with_bit_fragments([1u8, 2, 3], 2, 60);
+    }
+
     #[test]
     fn fragments_conditionally_change() {
         let mut b = Box::<[u64]>::with_zeroed_64bit_segments(2);
@@ -881,6 +1731,24 @@ mod tests {
         assert_eq!(dst.get_fragment(22, 3), 0);
     }
 
+    #[test]
+    fn fragments_binary_search() {
+        let mut b = Box::<[u64]>::with_zeroed_64bit_segments(2);
+        for (i, v) in [3u64, 7, 7, 20, 100, 101, 200, 255, 255, 255].into_iter().enumerate() {
+            b.init_fragment(i, v, 8);
+        }
+        assert_eq!(b.binary_search_fragment(3, 8, 10), Ok(0));
+        assert_eq!(b.binary_search_fragment(20, 8, 10), Ok(3));
+        assert_eq!(b.binary_search_fragment(200, 8, 10), Ok(6));
+        assert!(matches!(b.binary_search_fragment(7, 8, 10), Ok(1) | Ok(2)));
+        assert!(matches!(b.binary_search_fragment(255, 8, 10), Ok(7..=9)));
+        assert_eq!(b.binary_search_fragment(0, 8, 10), Err(0));
+        assert_eq!(b.binary_search_fragment(8, 8, 10), Err(3));
+        assert_eq!(b.binary_search_fragment(102, 8, 10), Err(6));
+        assert_eq!(b.binary_search_fragment(255, 8, 7), Err(7));
+        assert_eq!(b.binary_search_fragment(5, 8, 0), Err(0));
+    }
+
     #[test]
     fn bits() {
         let mut b = Box::<[u64]>::with_filled_64bit_segments(2);
@@ -908,6 +1776,594 @@ mod tests {
         assert!(b.get_bit(74));
     }
 
+    #[test]
+    fn split_count() {
+        let b = [0b1011_0110u64, u64::MAX, 0b1u64 << 63];
+        assert_eq!(b.split_count_ones(4, 8), (2, 3));
+        assert_eq!(b.split_count_ones(0, 8), (0, 5));
+        assert_eq!(b.split_count_ones(8, 8), (5, 0));
+        assert_eq!(b.split_count_ones(64, 128), (5, 64));
+        assert_eq!(b.split_count_ones(70, 192), (11, 59));
+        assert_eq!(b.split_count_ones(3, 100), (2, 39));
+        assert_eq!(b.split_count_ones(192, 192), (70, 0));
+        assert_eq!(b.split_count_ones(190, 191), (69, 0));
+    }
+
+    #[test]
+    fn count_with_padding() {
+        let mut b = Box::<[u64]>::with_zeroed_bits(130);
+        assert_eq!(b.count_bit_zeros_in(130), 130);
+        assert_eq!(b.count_bit_ones_in(130), 0);
+        b[2] = u64::MAX << 2;   // padding bits only
+        assert_eq!(b.count_bit_zeros_in(130), 130);
+        assert_eq!(b.count_bit_ones_in(130), 0);
+        b.set_bit(129);
+        b.set_bit(64);
+        assert_eq!(b.count_bit_zeros_in(130), 128);
+        assert_eq!(b.count_bit_ones_in(130), 2);
+        assert_eq!(b.count_bit_ones_in(128), 1);
+        assert_eq!(b.count_bit_zeros_in(0), 0);
+        assert_eq!(b.count_bit_ones_in(192), 64);
+    }
+
+    #[test]
+    fn count_in_range() {
+        let b = [u64::MAX << 58, 0b1011u64, u64::MAX];
+        assert_eq!(b.count_ones_in_range(60, 70), 4+3);
+        assert_eq!(b.count_ones_in_range(58, 64), 6);
+        assert_eq!(b.count_ones_in_range(64, 66), 2);
+        assert_eq!(b.count_ones_in_range(65, 67), 1);
+        assert_eq!(b.count_ones_in_range(3, 3), 0);
+        assert_eq!(b.count_ones_in_range(0, 64), 6);
+        assert_eq!(b.count_ones_in_range(0, 192), 6+3+64);
+        assert_eq!(b.count_ones_in_range(100, 192), 64);
+        assert_eq!(b.count_ones_in_range(192, 192), 0);
+        assert_eq!(b.count_ones_in_range(130, 191), 61);
+        assert_eq!(b.rank_ones(0), 0);
+        assert_eq!(b.rank_ones(59), 1);
+        assert_eq!(b.rank_ones(70), 6+3);
+        assert_eq!(b.rank_ones(192), 6+3+64);
+    }
+
+    #[test]
+    fn select() {
+        let b = [0b1010u64, 0, 1 << 63 | 1 << 7];
+        assert_eq!(b.select_ones(0), Some(1));
+        assert_eq!(b.select_ones(1), Some(3));
+        assert_eq!(b.select_ones(2), Some(128+7));
+        assert_eq!(b.select_ones(3), Some(191));
+        assert_eq!(b.select_ones(4), None);
+        assert_eq!(b.select_ones(1000), None);
+        assert_eq!([u64::MAX; 2].select_ones(127), Some(127));
+        assert_eq!([0u64; 2].select_ones(0), None);
+        for rank in 0..b.count_bit_ones() {
+            assert_eq!(b.rank_ones(b.select_ones(rank).unwrap()), rank);
+        }
+    }
+
+    #[test]
+    fn disjoint() {
+        let a = [0b1010u64, 0, 1 << 63];
+        assert!(a.is_disjoint(&[0b0101, u64::MAX, 0]));
+        assert!(a.is_disjoint(&[0, 0, 0]));
+        assert!(!a.is_disjoint(&[0b0100, 0, 1 << 63]));
+        assert!(!a.is_disjoint(&[0, 0, u64::MAX]));
+        assert!(!a.is_disjoint(&a));
+        assert!([0u64, 0].is_disjoint(&[0, 0]));
+    }
+
+    #[test]
+    fn hamming_and_equality() {
+        let a = [0b1010u64, u64::MAX, 0xFF];
+        assert_eq!(a.hamming_distance(&a), 0);
+        assert_eq!(a.hamming_distance(&[0b0101, u64::MAX, 0xFF]), 4);
+        assert_eq!(a.hamming_distance(&[0b1010, 0, 0]), 72);
+        assert!(a.equals_bits(&a, 192));
+        // padding beyond bit_len differs
+        let b = [0b1010u64, u64::MAX, 0xFF | (1 << 40)];
+        assert_eq!(a.hamming_distance(&b), 1);
+        assert!(a.equals_bits(&b, 168));
+        assert!(!a.equals_bits(&b, 169));
+        assert!(!a.equals_bits(&b, 192));
+        assert!(a.equals_bits(&b, 128));
+        assert!(a.equals_bits(&[0b1010u64], 64));
+        assert!(a.equals_bits(&[0b0010u64], 3));
+        assert!(!a.equals_bits(&[0b0010u64], 4));
+        assert!(a.equals_bits(&[], 0));
+    }
+
+    #[test]
+    fn bits_range_fill() {
+        let mut b = Box::<[u64]>::with_zeroed_64bit_segments(4);
+        b.set_bits_to(3, 7, true);
+        assert_eq!(b.as_ref(), [0b1111000, 0, 0, 0]);
+        b.set_bits_to(4, 4, true);
+        assert_eq!(b.as_ref(), [0b1111000, 0, 0, 0]);
+        b.set_bits_to(64, 128, true);
+        assert_eq!(b.as_ref(), [0b1111000, u64::MAX, 0, 0]);
+        b.set_bits_to(60, 200, true);
+        assert_eq!(b.as_ref(), [0b1111 << 60 | 0b1111000, u64::MAX, u64::MAX, 0b11111111]);
+        b.set_bits_to(5, 250, false);
+        assert_eq!(b.as_ref(), [0b11000, 0, 0, 0]);
+        b.set_bits_to(0, 256, true);
+        assert_eq!(b.as_ref(), [u64::MAX; 4]);
+        b.set_bits_to(127, 129, false);
+        assert_eq!(b.as_ref(), [u64::MAX, u64::MAX >> 1, u64::MAX - 1, u64::MAX]);
+    }
+
+    #[test]
+    fn bits_copy() {
+        let src = [0x0123_4567_89AB_CDEFu64, 0xFEDC_BA98_7654_3210, 0xA5A5_5A5A_F0F0_0F0F];
+        let naive = |dst_begin: usize, src_begin: usize, len: usize| {
+            let mut expected = Box::<[u64]>::with_filled_64bit_segments(4);
+            for i in 0..len { expected.set_bit_to(dst_begin + i, src.get_bit(src_begin + i)); }
+            let mut b = Box::<[u64]>::with_filled_64bit_segments(4);
+            b.copy_bits_from(dst_begin, &src, src_begin, len);
+            assert_eq!(b, expected, "dst_begin={dst_begin} src_begin={src_begin} len={len}");
+        };
+        naive(0, 0, 128);   // aligned
+        naive(64, 0, 192);   // aligned, multi-segment
+        naive(5, 0, 64);    // head misaligned
+        naive(0, 7, 100);   // source misaligned
+        naive(0, 0, 70);    // tail misaligned
+        naive(3, 61, 130);  // multi-segment, misaligned both
+        naive(100, 50, 0);
+        naive(255, 191, 1);
+    }
+
+    #[test]
+    fn fragments_iter() {
+        for v_size in [3u8, 13, 30] {
+            let count = 100;
+            let mut b = Box::<[u64]>::with_zeroed_bits(count * v_size as usize + 5);
+            for i in 0..count { b.init_fragment(i, (i as u64 * 0x9E37_79B9) & n_lowest_bits(v_size), v_size); }
+            let iter = b.fragments(v_size, count);
+            assert_eq!(iter.len(), count);
+            assert!(iter.eq((0..count).map(|i| b.get_fragment(i, v_size))), "v_size={v_size}");
+            assert_eq!(b.fragments(v_size, 0).next(), None);
+        }
+    }
+
+    #[test]
+    fn from_bits() {
+        let pattern = |i: usize| i.is_multiple_of(3) || i.is_multiple_of(7);
+        for len in [0, 1, 63, 64, 65, 200] {
+            let (b, b_len) = Box::<[u64]>::from_bits((0..len).map(pattern));
+            assert_eq!(b_len, len);
+            assert_eq!(b.len(), ceiling_div(len, 64));
+            assert!(b.bit_ones().eq((0..len).filter(|i| pattern(*i))), "len={len}");
+        }
+        assert_eq!(Box::<[u64]>::from_bits([true, false, true, true]), (vec![0b1101].into_boxed_slice(), 4));
+    }
+
+    #[test]
+    fn bit_vec_size() {
+        // GetSize for Box<[u64]> is provided by dyn_size_of and counts 8 bytes per segment,
+        // consistently with the fields of the structures that implement GetSize
+        use dyn_size_of::GetSize;
+        let b = Box::<[u64]>::with_zeroed_64bit_segments(3);
+        assert_eq!(b.size_bytes_dyn(), 24);
+        assert_eq!(b.size_bytes(), 24 + std::mem::size_of::<Box<[u64]>>());
+        assert_eq!(Box::<[u64]>::with_zeroed_bits(0).size_bytes_dyn(), 0);
+    }
+
+    #[test]
+    fn bitword_slice() {
+        let b = Box::<[u64]>::with_bitword_slice(&[1, 2, 3], 3);
+        assert_eq!(b.len(), 1);
+        assert_eq!([b.get_fragment(0, 3), b.get_fragment(1, 3), b.get_fragment(2, 3)], [1, 2, 3]);
+        let words: Vec<u64> = (0..100).map(|i| i * 1_000_003 % (1 << 21)).collect();
+        let b = Box::<[u64]>::with_bitword_slice(&words, 21);
+        assert_eq!(b.len(), ceiling_div(100 * 21, 64));
+        for (i, w) in words.iter().enumerate() { assert_eq!(b.get_fragment(i, 21), *w); }
+        assert!(Box::<[u64]>::with_bitword_slice(&[], 7).is_empty());
+    }
+
+    #[test]
+    fn sparse_fragments() {
+        let b = Box::<[u64]>::with_sparse_fragments([(3, 0b101), (40, 0b111), (0, 1)], 50, 3);
+        assert_eq!(b.len(), ceiling_div(50 * 3, 64));
+        for i in 0..50 {
+            let expected = match i { 0 => 1, 3 => 0b101, 40 => 0b111, _ => 0 };
+            assert_eq!(b.get_fragment(i, 3), expected, "i={i}");
+        }
+        assert_eq!(b.count_bit_ones(), 6);
+        assert_eq!(Box::<[u64]>::with_sparse_fragments([], 10, 7).as_ref(), [0, 0]);
+    }
+
+    fn count_fragments_eq_naive(bits: &[u64], value: u64, v_size: u8, count: usize) -> usize {
+        (0..count).filter(|i| bits.get_fragment(*i, v_size) == value).count()
+    }
+
+    #[test]
+    fn fragments_eq() {
+        let b = Box::<[u64]>::with_bit_fragments_exact([3u8, 0, 7, 3, 3, 255, 0, 3, 1, 3], 8);
+        assert_eq!(b.count_fragments_eq(3, 8, 10), 5);
+        assert_eq!(b.count_fragments_eq(3, 8, 4), 2);
+        assert_eq!(b.count_fragments_eq(0, 8, 10), 2);
+        assert_eq!(b.count_fragments_eq(255, 8, 10), 1);
+        assert_eq!(b.count_fragments_eq(256, 8, 10), 0);
+        let b = Box::<[u64]>::with_bit_fragments_exact((0..100u32).map(|i| (i * 7 % 5) as u8), 3);
+        assert_eq!(b.count_fragments_eq(2, 3, 100), 20);
+        assert_eq!(b.count_fragments_eq(2, 3, 7), 2);
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let bits: Vec<u64> = (0..20).map(|_| { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x & 0x0F3F_0F3F_0F3F_0F3F }).collect();
+        for v_size in [1, 2, 3, 4, 5, 8, 16, 32, 64] {
+            for count in [0, 1, 5, 20 * 64 / v_size as usize] {
+                for value in [0, 1, 3, 0x3F, 0x0F3F] {
+                    assert_eq!(bits.count_fragments_eq(value, v_size, count), count_fragments_eq_naive(&bits, value, v_size, count),
+                        "value={value} v_size={v_size} count={count}");
+                }
+            }
+            let value = bits.get_fragment(5, v_size);
+            assert_eq!(bits.count_fragments_eq(value, v_size, 20 * 64 / v_size as usize),
+                count_fragments_eq_naive(&bits, value, v_size, 20 * 64 / v_size as usize), "v_size={v_size}");
+        }
+    }
+
+    #[test]
+    fn xor_64_bits() {
+        let pattern = 0xF0F0_1234_5678_9ABCu64;
+        let mut b = [0x1111_1111_1111_1111u64, 0x2222_2222_2222_2222, 0x3333];
+        b.xor_bits(10, pattern, 64);
+        assert_eq!(b[0], 0x1111_1111_1111_1111 ^ (pattern << 10));
+        assert_eq!(b[1], 0x2222_2222_2222_2222 ^ (pattern >> 54));
+        assert_eq!(b[2], 0x3333);
+        assert_eq!(b.get_bits(10, 64), pattern ^ [0x1111_1111_1111_1111u64, 0x2222_2222_2222_2222].get_bits(10, 64));
+        b.xor_bits(10, pattern, 64);
+        assert_eq!(b, [0x1111_1111_1111_1111u64, 0x2222_2222_2222_2222, 0x3333]);
+        b.xor_bits(64, u64::MAX, 64);   // aligned
+        assert_eq!(b, [0x1111_1111_1111_1111u64, !0x2222_2222_2222_2222, 0x3333]);
+    }
+
+    #[test]
+    fn bit_one_runs() {
+        let mut b = Box::<[u64]>::with_zeroed_bits(4 * 64);
+        for i in (3..5).chain(60..70).chain(100..101).chain(127..256) { b.set_bit(i); }
+        assert_eq!(b.bit_one_runs().collect::<Vec<_>>(), [(3, 2), (60, 10), (100, 1), (127, 129)]);
+        assert_eq!([u64::MAX, u64::MAX].bit_one_runs().collect::<Vec<_>>(), [(0, 128)]);
+        assert_eq!([0, 1 << 63, 1, 0].bit_one_runs().collect::<Vec<_>>(), [(127, 2)]);
+        assert_eq!([0u64; 3].bit_one_runs().next(), None);
+        assert_eq!([0u64; 0].bit_one_runs().next(), None);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let bits: Box<[u64]> = (0..10).map(|_| { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x | x >> 3 }).collect();
+        let expanded: Vec<usize> = bits.bit_one_runs().flat_map(|(start, len)| start..start + len).collect();
+        assert_eq!(expanded, bits.bit_ones().collect::<Vec<_>>());
+        assert!(bits.bit_one_runs().zip(bits.bit_one_runs().skip(1)).all(|((s1, l1), (s2, _))| s1 + l1 < s2));
+    }
+
+    #[test]
+    fn rotate_fragment() {
+        let mut b = Box::<[u64]>::with_bit_fragments_exact([0b10110u8, 0b00011, 0b11111, 0b00001], 5);
+        b.rotate_fragment_left(0, 5, 1);
+        assert_eq!(b.get_fragment(0, 5), 0b01101);
+        b.rotate_fragment_left(1, 5, 4);
+        assert_eq!(b.get_fragment(1, 5), 0b10001);
+        b.rotate_fragment_left(3, 5, 7);    // 7 % 5 = 2
+        assert_eq!(b.get_fragment(3, 5), 0b00100);
+        b.rotate_fragment_left(2, 5, 5);
+        assert_eq!(b.get_fragment(2, 5), 0b11111);
+        assert_eq!(b.get_fragment(0, 5), 0b01101);
+        let mut b = vec![0u64; 2].into_boxed_slice();
+        b.set_fragment(5, 0b1000001, 13);   // crosses the segment boundary
+        b.rotate_fragment_left(5, 13, 7);
+        assert_eq!(b.get_fragment(5, 13), 0b1000_0001);
+    }
+
+    #[test]
+    fn get_byte_fragment() {
+        let b = [0x1234_5678_9ABC_DEF0u64, 0x0FED_CBA9_8765_4321, 0xF0E1_D2C3_B4A5_9687, 0x1111];
+        for bytes in 1..=8u8 {
+            for index in 0..b.len() * 8 / bytes as usize {
+                assert_eq!(b.get_byte_fragment(index, bytes), b.get_fragment(index, bytes * 8), "bytes={bytes}, index={index}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_byte_fragment_out_of_bounds() {
+        [0u64; 2].get_byte_fragment(4, 4);
+    }
+
+    #[test]
+    fn into_fragments() {
+        for v_size in [1, 3, 7, 13, 63, 64] {
+            let count = 300 * 8 / v_size as usize;
+            let values = (0..count as u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) & n_lowest_bits_0_64(v_size));
+            let b = Box::<[u64]>::with_bit_fragments(values, count, v_size);
+            let expected: Vec<u64> = b.fragments(v_size, count).collect();
+            let iter = b.into_fragments(v_size, count);
+            assert_eq!(iter.len(), count);
+            assert_eq!(iter.collect::<Vec<_>>(), expected, "v_size={v_size}");
+        }
+        let mut empty = Box::<[u64]>::with_zeroed_64bit_segments(1).into_fragments(5, 0);
+        assert_eq!(empty.next(), None);
+    }
+
+    #[test]
+    fn bit_ones_in_segments() {
+        let b = [0b1011u64, 0, 1 << 63 | 1, u64::MAX, 1 << 7];
+        let all: Vec<usize> = b.bit_ones().collect();
+        for middle in 0..=b.len() {
+            let mut union: Vec<usize> = b.bit_ones_in_segments(0, middle).collect();
+            union.extend(b.bit_ones_in_segments(middle, b.len()));
+            assert_eq!(union, all, "middle={middle}");
+        }
+        assert_eq!(b.bit_ones_in_segments(2, 3).collect::<Vec<_>>(), [128, 191]);
+        assert_eq!(b.bit_ones_in_segments(2, 3).rev().collect::<Vec<_>>(), [191, 128]);
+        assert_eq!(b.bit_ones_in_segments(3, 5).len(), 65);
+        assert_eq!(b.bit_ones_in_segments(4, 5).next_back(), Some(4 * 64 + 7));
+        assert_eq!(b.bit_ones_in_segments(1, 2).next(), None);
+    }
+
+    #[test]
+    fn try_with_segments() {
+        let zeroed = Box::<[u64]>::try_with_zeroed_64bit_segments(5).unwrap();
+        assert_eq!(&zeroed[..], &[0; 5]);
+        assert_eq!(Box::<[u64]>::try_with_filled_bits(130).unwrap(), Box::<[u64]>::with_filled_bits(130));
+        assert_eq!(Box::<[u64]>::try_with_zeroed_bits(64).unwrap().len(), 1);
+        assert!(Box::<[u64]>::try_with_zeroed_64bit_segments(0).unwrap().is_empty());
+        assert!(Box::<[u64]>::try_with_zeroed_64bit_segments(usize::MAX).is_err());   // capacity overflow
+    }
+
+    #[test]
+    fn try_init_fragment() {
+        for v_size in [1, 7, 13, 63, 64] {
+            let mut b = Box::<[u64]>::with_zeroed_bits(10 * v_size as usize);
+            let (v, other) = (n_lowest_bits_0_64(v_size), 1);
+            assert!(b.try_init_fragment(3, v, v_size), "v_size={v_size}");
+            assert!(!b.try_init_fragment(3, other, v_size), "v_size={v_size}");
+            assert_eq!(b.get_fragment(3, v_size), v);
+            assert!(b.try_init_fragment(4, other, v_size));
+            assert_eq!(b.get_fragment(4, v_size), other);
+            assert_eq!(b.get_fragment(2, v_size), 0);
+            assert_eq!(b.get_fragment(5, v_size), 0);
+        }
+    }
+
+    #[test]
+    fn gather_fragments() {
+        let b = [0x1234_5678_9ABC_DEF0u64, 0x0FED_CBA9_8765_4321, 0x1111];
+        for v_size in [1, 3, 7, 13, 63] {
+            let count = 3 * 64 / v_size as usize;
+            let indices: Vec<usize> = (0..count).rev().step_by(2).chain([0, count - 1]).collect();
+            let mut out = vec![0; indices.len()];
+            b.gather_fragments(&indices, v_size, &mut out);
+            for (o, i) in out.iter().zip(indices.iter()) {
+                assert_eq!(*o, b.get_fragment(*i, v_size), "v_size={v_size}, index={i}");
+            }
+        }
+        b.gather_fragments(&[], 5, &mut []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn gather_fragments_len_mismatch() {
+        [0u64; 2].gather_fragments(&[0, 1], 5, &mut [0]);
+    }
+
+    #[test]
+    fn fragmentwise_max_min() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut random = || { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x };   // xorshift
+        let a: Box<[u64]> = (0..5).map(|_| random()).collect();
+        let b: Box<[u64]> = (0..5).map(|_| random()).collect();
+        for v_size in [1, 7, 13, 63] {
+            let count = 5 * 64 / v_size as usize - 1;   // the last fragment is left unchanged
+            let (mut max, mut min) = (a.clone(), a.clone());
+            max.fragmentwise_max(&b, v_size, count);
+            min.fragmentwise_min(&b, v_size, count);
+            for i in 0..count {
+                let (va, vb) = (a.get_fragment(i, v_size), b.get_fragment(i, v_size));
+                assert_eq!(max.get_fragment(i, v_size), va.max(vb), "v_size={v_size} i={i}");
+                assert_eq!(min.get_fragment(i, v_size), va.min(vb), "v_size={v_size} i={i}");
+            }
+            for i in count * v_size as usize..5 * 64 {
+                assert_eq!(max.get_bit(i), a.get_bit(i), "v_size={v_size} i={i}");
+                assert_eq!(min.get_bit(i), a.get_bit(i), "v_size={v_size} i={i}");
+            }
+        }
+    }
+
+    #[test]
+    fn bitwise_assign() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut random = || { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x };   // xorshift
+        for len in [1, 3, 10] {
+            let a: Box<[u64]> = (0..len).map(|_| random()).collect();
+            let b: Box<[u64]> = (0..len).map(|_| random()).collect();
+            let (mut and, mut or, mut xor) = (a.clone(), a.clone(), a.clone());
+            and.and_assign(&b);
+            or.or_assign(&b);
+            xor.xor_assign(&b);
+            for i in 0..len * 64 {
+                assert_eq!(and.get_bit(i), a.get_bit(i) && b.get_bit(i), "i={i}");
+                assert_eq!(or.get_bit(i), a.get_bit(i) || b.get_bit(i), "i={i}");
+                assert_eq!(xor.get_bit(i), a.get_bit(i) != b.get_bit(i), "i={i}");
+            }
+        }
+    }
+
+    #[test]
+    fn find_first_bit() {
+        let b = [0b1011_0000u64, u64::MAX, 1 << 10];
+        assert_eq!(b.find_bit_one(0), Some(4));
+        assert_eq!(b.find_bit_one(5), Some(5));
+        assert_eq!(b.find_bit_one(6), Some(7));
+        assert_eq!(b.find_bit_one(8), Some(64));
+        assert_eq!(b.find_bit_one(129), Some(138));
+        assert_eq!(b.find_bit_one(139), None);
+        assert_eq!(b.find_bit_one(192), None);
+        assert_eq!(b.find_bit_zero(0), Some(0));
+        assert_eq!(b.find_bit_zero(4), Some(6));
+        assert_eq!(b.find_bit_zero(7), Some(8));
+        assert_eq!(b.find_bit_zero(64), Some(128));
+        assert_eq!(b.find_bit_zero(138), Some(139));
+        assert_eq!([u64::MAX, u64::MAX].find_bit_zero(3), None);
+        assert_eq!([0u64, 0].find_bit_one(3), None);
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        let mut before = Box::<[u64]>::with_zeroed_bits(200);
+        for i in [0, 5, 64, 100, 150] { before.set_bit(i); }
+        let mut after = before.clone();
+        for i in [5, 63, 64, 130, 199] { after.set_bit_to(i, !after.get_bit(i)); }
+        let (count, positions) = symmetric_diff(&before, &after);
+        assert_eq!(count, 5);
+        assert_eq!(positions.collect::<Vec<_>>(), [5, 63, 64, 130, 199]);
+        let (count, mut positions) = symmetric_diff(&before, &before);
+        assert_eq!(count, 0);
+        assert_eq!(positions.next(), None);
+    }
+
+    #[test]
+    fn bit_ones_double_ended() {
+        let b = [0b101u64, 0b10u64];
+        let mut iter = b.bit_ones();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next_back(), Some(65));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = b.bit_ones();
+        assert_eq!(iter.next_back(), Some(65));
+        assert_eq!(iter.next_back(), Some(2));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let b = [0x8000_0000_0000_0101u64, 0, 0b1001, 1 << 63];
+        assert!(b.bit_ones().rev().eq([255, 131, 128, 63, 8, 0]));
+        assert!(b.bit_zeros().rev().take(3).eq([254, 253, 252]));
+        let mut iter = b.bit_ones();
+        let mut yielded = Vec::new();
+        while let Some(i) = if yielded.len() % 2 == 0 { iter.next() } else { iter.next_back() } { yielded.push(i); }
+        assert_eq!(yielded, [0, 255, 8, 131, 63, 128]);
+        assert_eq!([0u64; 0].bit_ones().next_back(), None);
+    }
+
+    #[test]
+    fn merge_sorted() {
+        let a_values = [0u64, 3, 3, 10, 40, 63];
+        let b_values = [1u64, 3, 11, 12, 13, 50, 62, 63];
+        let a = Box::<[u64]>::with_bit_fragments_exact(a_values, 6);
+        let b = Box::<[u64]>::with_bit_fragments_exact(b_values, 6);
+        let merged = merge_sorted_fragments(&a, a_values.len(), &b, b_values.len(), 6);
+        let mut expected: Vec<u64> = a_values.iter().chain(&b_values).copied().collect();
+        expected.sort();
+        assert_eq!(merged.len(), ceiling_div(expected.len() * 6, 64));
+        assert!(merged.fragments(6, expected.len()).eq(expected));
+        assert_eq!(merge_sorted_fragments(&a, 0, &b, 2, 6), Box::<[u64]>::with_bit_fragments_exact([1u64, 3], 6));
+        assert_eq!(merge_sorted_fragments(&a, 2, &b, 0, 6), Box::<[u64]>::with_bit_fragments_exact([0u64, 3], 6));
+    }
+
+    #[test]
+    fn bits_try_get_set() {
+        let mut b = [0u64, 0];
+        assert_eq!(b.try_set_bits(123, 0b10110, 5), Some(()));
+        assert_eq!(b.try_get_bits(123, 5), Some(0b10110));
+        assert_eq!(b.try_set_bits(124, 0b11111, 5), None);
+        assert_eq!(b.try_get_bits(124, 5), None);
+        assert_eq!(b.try_get_bits(123, 5), Some(0b10110), "failed try_set_bits must not modify bits");
+        assert_eq!(b.try_set_bits(64, u64::MAX, 64), Some(()));
+        assert_eq!(b.try_get_bits(64, 64), Some(u64::MAX));
+        assert_eq!(b.try_get_bits(65, 64), None);
+        assert_eq!(b.try_set_bits(65, u64::MAX, 64), None);
+        assert_eq!(b.try_set_bits(usize::MAX, 1, 1), None);
+        assert_eq!(b.try_get_bits(128, 1), None);
+    }
+
+    #[test]
+    fn bits_u128() {
+        let mut b = Box::<[u64]>::with_zeroed_64bit_segments(5);
+        let v100 = 0xA_BCDE_F012_3456_789A_BCDE_F013u128;
+        for begin in [0, 1, 27, 63, 64, 100, 127, 191, 220] {
+            b.fill(0);
+            b.set_bits_u128(begin, v100, 100);
+            assert_eq!(b.get_bits_u128(begin, 100), v100, "begin={begin}");
+            assert_eq!(b.count_bit_ones(), v100.count_ones() as usize, "begin={begin}");
+            b.fill(u64::MAX);
+            b.set_bits_u128(begin, v100, 100);
+            assert_eq!(b.get_bits_u128(begin, 100), v100, "begin={begin}");
+            assert_eq!(b.count_bit_zeros(), v100.count_zeros() as usize - 28, "begin={begin}");
+        }
+        b.fill(0);
+        b.set_bits_u128(70, u128::MAX, 128);    // spans segments 1, 2 and 3
+        assert_eq!(b.as_ref(), [0, u64::MAX << 6, u64::MAX, n_lowest_bits(6), 0]);
+        assert_eq!(b.get_bits_u128(70, 128), u128::MAX);
+        assert_eq!(b.get_bits_u128(69, 128), u128::MAX << 1);
+        b.set_bits_u128(100, 0x1234_5678_9ABC_DEF0, 64);
+        assert_eq!(b.get_bits_u128(100, 64), 0x1234_5678_9ABC_DEF0);
+        assert_eq!(b.get_bits(100, 64), 0x1234_5678_9ABC_DEF0);
+    }
+
+    #[test]
+    fn bits_peek() {
+        let b = [0xFF00_0000_0000_00FFu64, 0xF0F0_F0F0_F0F0_F0F0];
+        assert_eq!(b.peek_bits(0, 8), 0xFF);
+        assert_eq!(b.peek_bits(60, 8), b.get_bits(60, 8));
+        assert_eq!(b.peek_bits(120, 8), 0xF0);
+        assert_eq!(b.peek_bits(124, 64), 0xF);
+        assert_eq!(b.peek_bits(64, 64), 0xF0F0_F0F0_F0F0_F0F0);
+        assert_eq!(b.peek_bits(100, 64), 0xF0F0_F0F0_F0F0_F0F0 >> 36);
+        assert_eq!(b.peek_bits(128, 5), 0);
+        assert_eq!(b.peek_bits(1000, 5), 0);
+    }
+
+    #[test]
+    fn bits_reverse() {
+        let bits = [0x0123_4567_89AB_CDEFu64, 0xFEDC_BA98_7654_3210, 0xA5A5_5A5A_F0F0_0F0F, 0x1357_9BDF_2468_ACE0];
+        let naive = |begin: usize, end: usize| {
+            let mut expected = bits;
+            for i in begin..end { expected.set_bit_to(i, bits.get_bit(begin + end - 1 - i)); }
+            let mut b = bits;
+            b.reverse_bit_range(begin, end);
+            assert_eq!(b, expected, "begin={begin} end={end}");
+            b.reverse_bit_range(begin, end);
+            assert_eq!(b, bits, "begin={begin} end={end}");
+        };
+        naive(10, 10);      // empty
+        naive(77, 78);      // single bit
+        naive(3, 40);       // within single segment
+        naive(64, 128);     // whole segment
+        naive(0, 256);      // whole vector
+        naive(5, 250);      // several segments, misaligned both
+        naive(60, 70);      // across segments boundary
+        naive(1, 130);
+    }
+
+    #[test]
+    fn bits_64() {
+        let v = 0xF0E1_D2C3_B4A5_9687u64;
+        let mut b = Box::<[u64]>::with_zeroed_64bit_segments(3);
+        b.set_bits(5, v, 64);
+        assert_eq!(b.get_bits(5, 64), v);
+        assert_eq!(b.try_get_bits(5, 64), Some(v));
+        assert_eq!(unsafe { b.get_bits_unchecked(5, 64) }, v);
+        assert_eq!(b.get_bits(0, 5), 0);
+        assert_eq!(b.get_bits(69, 59), 0);
+        b.set_bits(5, u64::MAX, 64);
+        assert_eq!(b.as_ref(), [u64::MAX << 5, 0b11111, 0]);
+        b.xor_bits(5, v, 64);
+        assert_eq!(b.get_bits(5, 64), !v);
+        assert_eq!(b.get_bits(0, 5), 0);
+        assert_eq!(b.get_bits(69, 59), 0);
+
+        b.set_bits(128, v, 64);
+        assert_eq!(b[2], v);
+        assert_eq!(b.get_bits(128, 64), v);
+        unsafe { b.set_bits_unchecked(128, !v, 64) };
+        assert_eq!(b.get_bits(128, 64), !v);
+        b.xor_bits(128, u64::MAX, 64);
+        assert_eq!(b.get_bits(128, 64), v);
+        assert_eq!(b.try_get_bits(129, 64), None);
+    }
+
     #[test]
     fn iterators() {
         let b = [0b101u64, 0b10u64];
@@ -923,6 +2379,17 @@ mod tests {
         assert_eq!(ones.len(), 0);
         assert_eq!(ones.next(), None);
         assert_eq!(ones.len(), 0);
+        let mut zeros = b.bit_zeros();
+        assert_eq!(zeros.len(), 2*64-3);
+        assert_eq!(zeros.next(), Some(1));
+        assert_eq!(zeros.next(), Some(3));
+        assert_eq!(zeros.len(), 2*64-5);
+        assert_eq!(zeros.nth(60), Some(64));   // skip 4..=63
+        assert_eq!(zeros.next(), Some(66));
+        assert_eq!(zeros.len(), 61);
+        assert_eq!(zeros.next_back(), Some(127));
+        assert_eq!([0b101u64].bit_zeros().collect::<Vec<_>>(), [1].into_iter().chain(3..64).collect::<Vec<_>>());
+        assert_eq!([u64::MAX].bit_zeros().next(), None);
         let mut all = b.bit_iter();
         assert_eq!(all.len(), 2*64);
         assert_eq!(all.next(), Some(true));
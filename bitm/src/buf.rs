@@ -0,0 +1,98 @@
+use dyn_size_of::GetSize;
+use super::{n_lowest_bits_0_64, BitAccess, BitSlice};
+
+/// Growable bit vector, to which bits and fragments of up to 64 bits can be appended.
+///
+/// Unlike the fixed-size `Box<[u64]>` constructors of [`BitVec`](crate::BitVec),
+/// the number of bits does not have to be known in advance.
+/// The exact number of bits pushed is tracked and the unused bits of the last segment are always zeroed.
+#[derive(Clone, Default)]
+pub struct BitVecBuf {
+    /// 64-bit segments that store the bits.
+    segments: Vec<u64>,
+    /// Number of bits.
+    bit_len: usize
+}
+
+impl GetSize for BitVecBuf {
+    fn size_bytes_dyn(&self) -> usize { self.segments.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl BitVecBuf {
+    /// Returns empty buffer.
+    #[inline] pub fn new() -> Self { Self::default() }
+
+    /// Returns empty buffer, with space for at least `bit_capacity` bits.
+    #[inline] pub fn with_capacity(bit_capacity: usize) -> Self {
+        Self { segments: Vec::with_capacity(bit_capacity.div_ceil(64)), bit_len: 0 }
+    }
+
+    /// Returns the number of bits.
+    #[inline] pub fn len(&self) -> usize { self.bit_len }
+
+    /// Returns whether `self` contains no bits.
+    #[inline] pub fn is_empty(&self) -> bool { self.bit_len == 0 }
+
+    /// Appends the given `bit`.
+    #[inline] pub fn push_bit(&mut self, bit: bool) {
+        self.push_fragment(bit as u64, 1)
+    }
+
+    /// Appends the fragment `v` of `v_size` (in range [0, 64]) bits. Panics if `v` does not fit in `v_size` bits.
+    pub fn push_fragment(&mut self, v: u64, v_size: u8) {
+        assert!(v <= n_lowest_bits_0_64(v_size), "BitVecBuf: {v} does not fit in {v_size} bits");
+        let begin = self.bit_len;
+        self.bit_len += v_size as usize;
+        self.segments.resize(self.bit_len.div_ceil(64), 0);
+        if v_size != 0 { self.segments.init_bits(begin, v, v_size); }
+    }
+
+    /// Returns the segments that store the bits.
+    #[inline] pub fn as_slice(&self) -> &[u64] { &self.segments }
+
+    /// Returns view of all bits of `self`.
+    #[inline] pub fn as_bit_slice(&self) -> BitSlice<'_> {
+        BitSlice::new(&self.segments, 0, self.bit_len)
+    }
+
+    /// Returns the segments that store the bits and the number of bits.
+    #[inline] pub fn into_parts(self) -> (Box<[u64]>, usize) {
+        (self.segments.into_boxed_slice(), self.bit_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn push_mixed_width_fragments() {
+        let fragments = [(1u64, 1u8), (0b101, 3), (0, 0), (u64::MAX, 64), (0x1234, 13), (0, 5), (u64::MAX >> 1, 63), (1, 1)];
+        let mut buf = BitVecBuf::new();
+        assert!(buf.is_empty());
+        for (v, v_size) in fragments { buf.push_fragment(v, v_size); }
+        buf.push_bit(false);
+        buf.push_bit(true);
+        assert_eq!(buf.len(), 1 + 3 + 64 + 13 + 5 + 63 + 1 + 2);
+        assert_eq!(buf.as_slice().len(), buf.len().div_ceil(64));
+        let bits = buf.as_bit_slice();
+        let mut begin = 0;
+        for (v, v_size) in fragments {
+            assert_eq!(bits.get_bits(begin, v_size), v & n_lowest_bits_0_64(v_size), "begin={begin}");
+            begin += v_size as usize;
+        }
+        assert!(!bits.get_bit(begin) && bits.get_bit(begin + 1));
+        assert_eq!(bits.try_get_bit(begin + 2), None);
+        let (segments, len) = buf.into_parts();
+        assert_eq!(segments.count_bit_ones(), 1 + 2 + 64 + 5 + 63 + 1 + 1);
+        assert_eq!(len, begin + 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fragment_too_large() {
+        BitVecBuf::with_capacity(10).push_fragment(8, 3);
+    }
+}
@@ -0,0 +1,175 @@
+use dyn_size_of::GetSize;
+use super::{BitAccess, Rank, Select};
+
+/// Number of bits in each chunk of [`ChunkedBitVec`].
+const CHUNK_BITS: usize = 1 << 16;
+
+/// Number of 64-bit segments in each chunk of [`ChunkedBitVec`].
+const CHUNK_SEGMENTS: usize = CHUNK_BITS / 64;
+
+/// Content of a single chunk of [`ChunkedBitVec`].
+enum Chunk {
+    /// Bit vector of the chunk.
+    Dense(Box<[u64]>),
+    /// Sorted positions (within the chunk) of ones.
+    Sparse(Box<[u16]>)
+}
+
+impl Chunk {
+    /// Returns the chunk that represents `segments`, choosing smaller of dense and sparse representations.
+    fn new(segments: &[u64]) -> Self {
+        let ones = segments.count_bit_ones();
+        if ones * 16 < segments.len() * 64 {
+            Self::Sparse(segments.bit_ones().map(|i| i as u16).collect())
+        } else {
+            Self::Dense(segments.into())
+        }
+    }
+
+    /// Gets bit with given index `bit_nr` (within the chunk).
+    #[inline] fn get_bit(&self, bit_nr: usize) -> bool {
+        match self {
+            Self::Dense(segments) => segments.get_bit(bit_nr),
+            Self::Sparse(positions) => positions.binary_search(&(bit_nr as u16)).is_ok()
+        }
+    }
+
+    /// Returns the number of ones in first `index` bits of the chunk.
+    #[inline] fn rank(&self, index: usize) -> usize {
+        match self {
+            Self::Dense(segments) => segments.rank_ones(index),
+            Self::Sparse(positions) => positions.partition_point(|p| (*p as usize) < index)
+        }
+    }
+
+    /// Returns the position (within the chunk) of the `rank`-th one or [`None`] if there are no such many ones.
+    #[inline] fn select(&self, rank: usize) -> Option<usize> {
+        match self {
+            Self::Dense(segments) => segments.select_ones(rank),
+            Self::Sparse(positions) => positions.get(rank).map(|p| *p as usize)
+        }
+    }
+}
+
+impl GetSize for Chunk {
+    fn size_bytes_dyn(&self) -> usize {
+        match self {
+            Self::Dense(segments) => segments.size_bytes_dyn(),
+            Self::Sparse(positions) => positions.size_bytes_dyn()
+        }
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+/// Bit vector for data that contain both dense and sparse regions, similar to Roaring bitmaps.
+///
+/// The bit space is divided into chunks of 2<sup>16</sup> bits.
+/// Each chunk is stored either as a bit vector or as a sorted list of positions of its ones,
+/// depending on which representation is smaller.
+/// Supports [`Rank`] and [`Select`] queries.
+pub struct ChunkedBitVec {
+    /// Chunks of the bit vector.
+    chunks: Box<[Chunk]>,
+    /// Number of ones before each chunk and total number of ones (as the last element).
+    ones_before: Box<[usize]>,
+    /// Number of bits.
+    len: usize
+}
+
+impl GetSize for ChunkedBitVec {
+    fn size_bytes_dyn(&self) -> usize {
+        self.chunks.size_bytes_dyn() + self.ones_before.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl ChunkedBitVec {
+    /// Constructs [`ChunkedBitVec`] with the same content as the bit vector `content`.
+    pub fn build(content: &[u64]) -> Self {
+        let chunks: Box<[Chunk]> = content.chunks(CHUNK_SEGMENTS).map(Chunk::new).collect();
+        let mut ones_before = Vec::with_capacity(chunks.len() + 1);
+        let mut ones = 0;
+        ones_before.push(ones);
+        for segments in content.chunks(CHUNK_SEGMENTS) {
+            ones += segments.count_bit_ones();
+            ones_before.push(ones);
+        }
+        Self { chunks, ones_before: ones_before.into_boxed_slice(), len: content.len() * 64 }
+    }
+
+    /// Returns the number of bits.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether `self` contains no bits.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of ones (set bits).
+    #[inline] pub fn count_bit_ones(&self) -> usize { *self.ones_before.last().unwrap() }
+
+    /// Gets bit with given index `bit_nr`. Panics if `bit_nr` is out of bounds.
+    #[inline] pub fn get_bit(&self, bit_nr: usize) -> bool {
+        self.chunks[bit_nr / CHUNK_BITS].get_bit(bit_nr % CHUNK_BITS)
+    }
+}
+
+impl Rank for ChunkedBitVec {
+    fn try_rank(&self, index: usize) -> Option<usize> {
+        if index > self.len { return None; }
+        let chunk_index = index / CHUNK_BITS;
+        let before = self.ones_before[chunk_index];
+        Some(match self.chunks.get(chunk_index) {
+            Some(chunk) => before + chunk.rank(index % CHUNK_BITS),
+            None => before  // index == self.len at the chunk boundary
+        })
+    }
+}
+
+impl Select for ChunkedBitVec {
+    fn try_select(&self, rank: usize) -> Option<usize> {
+        if rank >= self.count_bit_ones() { return None; }
+        let chunk_index = self.ones_before.partition_point(|r| *r <= rank) - 1;
+        Some(chunk_index * CHUNK_BITS + self.chunks[chunk_index].select(rank - self.ones_before[chunk_index])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::BitVec;
+
+    #[test]
+    fn mixed_density() {
+        let len = 5 * CHUNK_BITS + 1024;
+        let mut dense = Box::<[u64]>::with_zeroed_bits(len);
+        for i in (0..2 * CHUNK_BITS).step_by(3) { dense.set_bit(i); }         // two dense chunks
+        for i in (2 * CHUNK_BITS..len).step_by(1001) { dense.set_bit(i); }   // sparse chunks
+        dense.set_bit(len - 1);
+        let chunked = ChunkedBitVec::build(&dense);
+        assert_eq!(chunked.len(), len);
+        assert_eq!(chunked.count_bit_ones(), dense.count_bit_ones());
+        assert!(chunked.size_bytes() < dense.size_bytes() / 2);
+        let mut rank = 0;
+        for i in 0..len {
+            assert_eq!(chunked.get_bit(i), dense.get_bit(i), "i={i}");
+            assert_eq!(chunked.rank(i), rank, "i={i}");
+            if dense.get_bit(i) {
+                assert_eq!(chunked.select(rank), i, "rank={rank}");
+                rank += 1;
+            }
+        }
+        assert_eq!(chunked.try_rank(len), Some(rank));
+        assert_eq!(chunked.try_rank(len + 1), None);
+        assert_eq!(chunked.try_select(rank), None);
+    }
+
+    #[test]
+    fn chunk_aligned_len() {
+        let bits = vec![u64::MAX; CHUNK_SEGMENTS].into_boxed_slice();
+        let chunked = ChunkedBitVec::build(&bits);
+        assert_eq!(chunked.try_rank(CHUNK_BITS), Some(CHUNK_BITS));
+        assert_eq!(chunked.try_select(CHUNK_BITS - 1), Some(CHUNK_BITS - 1));
+        assert!(ChunkedBitVec::build(&[]).is_empty());
+        assert_eq!(ChunkedBitVec::build(&[]).try_rank(0), Some(0));
+    }
+}
@@ -0,0 +1,91 @@
+use super::{BitAccess, BitVec};
+
+/// Bit vector that caches the number of its ones (set bits) and updates it on each modification.
+pub struct CountedBitVec {
+    /// The bit vector.
+    bits: Box<[u64]>,
+    /// Number of ones in `bits`.
+    ones: usize
+}
+
+impl From<Box<[u64]>> for CountedBitVec {
+    fn from(bits: Box<[u64]>) -> Self {
+        let ones = bits.count_bit_ones();
+        Self { bits, ones }
+    }
+}
+
+impl CountedBitVec {
+    /// Returns vector of `bit_len` bits, all set to `0`.
+    pub fn with_zeroed_bits(bit_len: usize) -> Self {
+        Self { bits: Box::with_zeroed_bits(bit_len), ones: 0 }
+    }
+
+    /// Returns the (cached) number of ones (set bits).
+    #[inline] pub fn count_ones(&self) -> usize { self.ones }
+
+    /// Returns the underlying bit vector.
+    #[inline] pub fn as_bits(&self) -> &[u64] { &self.bits }
+
+    /// Returns the underlying bit vector.
+    #[inline] pub fn into_bits(self) -> Box<[u64]> { self.bits }
+
+    /// Gets bit with given index `bit_nr`. Panics if `bit_nr` is out of bounds.
+    #[inline] pub fn get_bit(&self, bit_nr: usize) -> bool { self.bits.get_bit(bit_nr) }
+
+    /// Set bit with given index `bit_nr` to `value` (`1` if `true`, `0` otherwise). Panics if `bit_nr` is out of bounds.
+    pub fn set_bit_to(&mut self, bit_nr: usize, value: bool) {
+        let old = self.bits.get_bit(bit_nr);
+        self.bits.set_bit_to(bit_nr, value);
+        self.ones = self.ones + value as usize - old as usize;
+    }
+
+    /// Set bit with given index `bit_nr` to `1`. Panics if `bit_nr` is out of bounds.
+    #[inline] pub fn set_bit(&mut self, bit_nr: usize) { self.set_bit_to(bit_nr, true) }
+
+    /// Set bit with given index `bit_nr` to `0`. Panics if `bit_nr` is out of bounds.
+    #[inline] pub fn clear_bit(&mut self, bit_nr: usize) { self.set_bit_to(bit_nr, false) }
+
+    /// Sets bits `[begin, begin+len)` to the content of `v`. Panics if the range is out of bounds.
+    pub fn set_bits(&mut self, begin: usize, v: u64, len: u8) {
+        let old = self.bits.get_bits(begin, len);
+        self.bits.set_bits(begin, v, len);
+        self.ones = self.ones + self.bits.get_bits(begin, len).count_ones() as usize - old.count_ones() as usize;
+    }
+
+    /// Sets bits [`index*v_size`, `index*v_size+v_size`) to `v`. Panics if the range is out of bounds.
+    #[inline] pub fn set_fragment(&mut self, index: usize, v: u64, v_size: u8) {
+        self.set_bits(index * v_size as usize, v, v_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn cached_count() {
+        let mut b = CountedBitVec::with_zeroed_bits(200);
+        assert_eq!(b.count_ones(), 0);
+        b.set_bit(3);
+        b.set_bit(3);
+        b.set_bit(130);
+        assert_eq!(b.count_ones(), 2);
+        b.clear_bit(4);
+        b.clear_bit(3);
+        assert_eq!(b.count_ones(), 1);
+        b.set_bits(60, u64::MAX, 64);
+        assert_eq!(b.count_ones(), 65);
+        b.set_fragment(2, 0b1010, 4);   // bits 8..12
+        assert_eq!(b.count_ones(), 67);
+        b.set_fragment(16, 0, 4);   // bits 64..68
+        assert_eq!(b.count_ones(), 63);
+        b.set_bits(120, 0b111, 12);     // bits 120..132, bits 120..124 and 130 were set
+        assert_eq!(b.count_ones(), 61);
+        b.set_bit_to(199, true);
+        assert_eq!(b.count_ones(), b.as_bits().count_bit_ones());
+        let from_bits = CountedBitVec::from(b.into_bits());
+        assert_eq!(from_bits.count_ones(), 62);
+    }
+}
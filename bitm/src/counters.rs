@@ -0,0 +1,101 @@
+use super::{n_lowest_bits, BitAccess, BitVec};
+
+/// Array of counters, each stored in fragment of given bit size, that caches the sum of all counters.
+pub struct PackedCounters {
+    /// Counters, stored as fragments of `bits_per_counter` bits.
+    counters: Box<[u64]>,
+    /// Number of counters.
+    len: usize,
+    /// Size of each counter in bits, in range [0, 63].
+    bits_per_counter: u8,
+    /// Sum of all counters.
+    total: u64
+}
+
+impl PackedCounters {
+    /// Returns `len` counters of `bits_per_counter` (in range [0, 63]) bits each, all initialized to zero.
+    pub fn with_zeroed(len: usize, bits_per_counter: u8) -> Self {
+        Self { counters: Box::with_zeroed_bits(len * bits_per_counter as usize), len, bits_per_counter, total: 0 }
+    }
+
+    /// Returns `len` counters of `bits_per_counter` (in range [0, 63]) bits each, stored as fragments of `counters`.
+    /// Panics if `counters` is too short.
+    pub fn from_bits(counters: Box<[u64]>, len: usize, bits_per_counter: u8) -> Self {
+        let total = (0..len).map(|i| counters.get_fragment(i, bits_per_counter)).sum();
+        Self { counters, len, bits_per_counter, total }
+    }
+
+    /// Returns the number of counters.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether there are no counters.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the size of each counter in bits.
+    #[inline] pub fn bits_per_counter(&self) -> u8 { self.bits_per_counter }
+
+    /// Returns the (cached) sum of all counters.
+    #[inline] pub fn total(&self) -> u64 { self.total }
+
+    /// Returns the bit vector that stores the counters.
+    #[inline] pub fn as_bits(&self) -> &[u64] { &self.counters }
+
+    /// Returns the bit vector that stores the counters.
+    #[inline] pub fn into_bits(self) -> Box<[u64]> { self.counters }
+
+    /// Returns the value of the `index`-th counter. Panics if `index` is out of bounds.
+    #[inline] pub fn get(&self, index: usize) -> u64 {
+        assert!(index < self.len, "PackedCounters index {index} out of bounds");
+        self.counters.get_fragment(index, self.bits_per_counter)
+    }
+
+    /// Sets the value of the `index`-th counter to `value` and updates the total.
+    /// Panics if `index` is out of bounds or `value` does not fit in the counter.
+    pub fn set(&mut self, index: usize, value: u64) {
+        assert!(value <= n_lowest_bits(self.bits_per_counter), "PackedCounters value {value} does not fit in {} bits", self.bits_per_counter);
+        let old = self.get(index);
+        self.total = self.total - old + value;
+        self.counters.set_fragment(index, value, self.bits_per_counter);
+    }
+
+    /// Adds `delta` to the `index`-th counter, updates the total, and returns the new value of the counter.
+    /// Panics if `index` is out of bounds or the new value does not fit in the counter.
+    pub fn add_to_fragment(&mut self, index: usize, delta: i64) -> u64 {
+        let new = self.get(index).checked_add_signed(delta)
+            .expect("PackedCounters counter cannot be decreased below zero");
+        self.set(index, new);
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn cached_total() {
+        let mut counters = PackedCounters::with_zeroed(100, 7);
+        assert_eq!(counters.total(), 0);
+        let mut x = 12345u64;
+        for _ in 0..10000 {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let index = (x >> 40) as usize % counters.len();
+            let current = counters.get(index) as i64;
+            let delta = if x & (1 << 20) == 0 { ((x >> 10) % 10) as i64 } else { -(((x >> 10) % 10) as i64) };
+            let delta = delta.clamp(-current, 127 - current);
+            assert_eq!(counters.add_to_fragment(index, delta), (current + delta) as u64);
+        }
+        let bits = counters.as_bits();
+        assert_eq!(counters.total(), (0..100).map(|i| bits.get_fragment(i, 7)).sum::<u64>());
+        let total = counters.total();
+        assert_eq!(PackedCounters::from_bits(counters.into_bits(), 100, 7).total(), total);
+    }
+
+    #[test]
+    #[should_panic]
+    fn below_zero() {
+        let mut counters = PackedCounters::with_zeroed(3, 4);
+        counters.add_to_fragment(1, -1);
+    }
+}
@@ -0,0 +1,67 @@
+//! Reading and writing bit vectors with their lengths.
+//!
+//! The format is portable: the length (in bits) and then the segments are written as little-endian `u64` values.
+
+use std::io::{self, Read, Write};
+use super::{ceiling_div, BitVec};
+
+/// Extends `[u64]` with a method for writing it as a bit vector of given length.
+pub trait WriteBits {
+    /// Writes the first `bit_len` bits of `self`, preceded by `bit_len`, to `out`.
+    /// The bits can be read back by [`read_bits`].
+    /// Panics if `bit_len` is out of bounds.
+    fn write_bits<W: Write + ?Sized>(&self, bit_len: usize, out: &mut W) -> io::Result<()>;
+}
+
+impl WriteBits for [u64] {
+    fn write_bits<W: Write + ?Sized>(&self, bit_len: usize, out: &mut W) -> io::Result<()> {
+        let segments = &self[..ceiling_div(bit_len, 64)];
+        out.write_all(&(bit_len as u64).to_le_bytes())?;
+        segments.iter().try_for_each(|s| out.write_all(&s.to_le_bytes()))
+    }
+}
+
+/// Reads `u64` in little-endian byte order from `input`.
+fn read_u64<R: Read + ?Sized>(input: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Reads bit vector written by [`WriteBits::write_bits`] from `input`. Returns the vector and its length in bits.
+pub fn read_bits<R: Read + ?Sized>(input: &mut R) -> io::Result<(Box<[u64]>, usize)> {
+    let invalid_len = || io::Error::new(io::ErrorKind::InvalidData, "read_bits: bit vector too long to allocate");
+    let bit_len = usize::try_from(read_u64(input)?).map_err(|_| invalid_len())?;
+    let mut result = Box::<[u64]>::try_with_zeroed_bits(bit_len).map_err(|_| invalid_len())?;
+    for segment in result.iter_mut() { *segment = read_u64(input)?; }
+    Ok((result, bit_len))
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bits = [0x0123_4567_89AB_CDEFu64, 0xFEDC_BA98_7654_3210, 0xFFFF];
+        for bit_len in [0, 1, 64, 65, 150] {
+            let mut buff = Vec::new();
+            bits.write_bits(bit_len, &mut buff).unwrap();
+            assert_eq!(buff.len(), 8 + 8 * ceiling_div(bit_len, 64));
+            let (read, read_len) = read_bits(&mut &buff[..]).unwrap();
+            assert_eq!(read_len, bit_len);
+            assert_eq!(read.as_ref(), &bits[..ceiling_div(bit_len, 64)]);
+        }
+        let mut buff = Vec::new();
+        bits.write_bits(65, &mut buff).unwrap();
+        assert_eq!(&buff[..9], [65, 0, 0, 0, 0, 0, 0, 0, 0xEF]);
+        assert!(read_bits(&mut &buff[..buff.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn too_long() {
+        let buff = u64::MAX.to_le_bytes();
+        assert_eq!(read_bits(&mut &buff[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -9,8 +9,30 @@ pub use rank_select::{RankSimple, ArrayWithRankSimple, RankSelect101111, ArrayWi
 mod bitvec;
 pub use bitvec::*;
 
-/// Returns ceil of `n/d`.
-#[inline(always)] pub const fn ceiling_div(n: usize, d: usize) -> usize { (n+d-1)/d }
+mod popcount;
+
+mod counters;
+pub use counters::PackedCounters;
+
+mod counted;
+pub use counted::CountedBitVec;
+
+mod packed;
+pub use packed::PackedArray;
+
+mod slice;
+pub use slice::BitSlice;
+
+mod buf;
+pub use buf::BitVecBuf;
+
+mod chunked;
+pub use chunked::ChunkedBitVec;
+
+pub mod io;
+
+/// Returns ceil of `n/d`. Does not overflow, even for `n` close to [`usize::MAX`].
+#[inline(always)] pub const fn ceiling_div(n: usize, d: usize) -> usize { n/d + !n.is_multiple_of(d) as usize }
 
 /// Returns the largest `how_many`-bit number, i.e. 0..01..1 mask with `how_many` ones. `how_many` must be in range [0, 63].
 #[inline(always)] pub const fn n_lowest_bits(how_many: u8) -> u64 { (1u64 << how_many).wrapping_sub(1) }
@@ -28,6 +50,12 @@ pub use bitvec::*;
     n_lowest_bits(how_many)
 }
 
+/// Returns 1..10..0 mask with `how_many` ones at the most significant positions. `how_many` must be in range [0, 64].
+#[inline(always)] pub const fn n_highest_bits(how_many: u8) -> u64 {
+    if how_many == 0 { return 0; }
+    u64::MAX << (64-how_many)
+}
+
 /// Calculates the minimal number of bits needed to store values from `0` to given `max_value`.
 ///
 /// # Example
@@ -50,6 +78,39 @@ pub use bitvec::*;
     max_value.into().checked_ilog2().map_or(0, |v| v as u8+1)
 }
 
+/// Returns the minimal fragment size (in bits) that allows storing any of the given `values` without loss,
+/// i.e. [`bits_to_store`] of their maximum (`0` if `values` is empty or contains only zeros).
+///
+/// # Example
+///
+/// ```
+/// use bitm::min_fragment_width;
+///
+/// assert_eq!(min_fragment_width(&[]), 0);
+/// assert_eq!(min_fragment_width(&[3, 0, 5]), 3);
+/// ```
+#[inline] pub fn min_fragment_width(values: &[u64]) -> u8 {
+    bits_to_store(values.iter().fold(0, |max, v| max.max(*v)))
+}
+
+/// Checks whether fragments of `v_size` bits fit in values of type `T`, i.e. whether `v_size <= 8 * size_of::<T>()`.
+///
+/// Evaluated in a const context, it fails the compilation if the fragments do not fit.
+/// Otherwise, it panics in such a case.
+///
+/// # Example
+///
+/// ```
+/// const _: () = bitm::assert_width_fits::<u8>(8);
+/// ```
+///
+/// ```compile_fail
+/// const _: () = bitm::assert_width_fits::<u8>(9);
+/// ```
+#[inline(always)] pub const fn assert_width_fits<T>(v_size: u8) {
+    assert!(v_size as usize <= 8 * std::mem::size_of::<T>(), "fragment width exceeds the size of the target type");
+}
+
 /// Read at least 57 bits from `ptr`, beginning from `first_bit`.
 #[inline(always)]
 pub unsafe fn get_bits57(ptr: *const u8, first_bit: usize) -> u64 {
@@ -124,6 +185,12 @@ mod tests {
         assert_eq!(ceiling_div(8, 2), 4);
         assert_eq!(ceiling_div(9, 2), 5);
         assert_eq!(ceiling_div(10, 3), 4);
+        assert_eq!(ceiling_div(0, 3), 0);
+        assert_eq!(ceiling_div(usize::MAX, 1), usize::MAX);
+        assert_eq!(ceiling_div(usize::MAX, 2), usize::MAX/2 + 1);
+        assert_eq!(ceiling_div(usize::MAX, 64), usize::MAX/64 + 1);
+        assert_eq!(ceiling_div(usize::MAX - 1, usize::MAX), 1);
+        assert_eq!(ceiling_div(usize::MAX, usize::MAX), 1);
     }
 
     #[test]
@@ -134,6 +201,15 @@ mod tests {
         assert_eq!(n_lowest_bits(0), 0);
     }
 
+    #[test]
+    fn test_n_highest() {
+        assert_eq!(n_highest_bits(64), u64::MAX);
+        assert_eq!(n_highest_bits(63), u64::MAX<<1);
+        assert_eq!(n_highest_bits(1), 1<<63);
+        assert_eq!(n_highest_bits(0), 0);
+        for n in 0..=64 { assert_eq!(n_highest_bits(n), !n_lowest_bits_0_64(64-n)); }
+    }
+
     #[test]
     fn test_bits_to_store() {
         assert_eq!(bits_to_store(0u32), 0);
@@ -149,5 +225,38 @@ mod tests {
         assert_eq!(bits_to_store(u32::MAX-1), 32);
         assert_eq!(bits_to_store(u32::MAX), 32);
         assert_eq!(bits_to_store(u64::MAX), 64);
+        for (value, bits) in [(0u8, 0), (1, 1), (7, 3), (8, 4), (u8::MAX, 8)] {
+            assert_eq!(bits_to_store(value), bits);
+            assert_eq!(bits_to_store(value as u16), bits);
+            assert_eq!(bits_to_store(value as u64), bits);
+        }
+        assert_eq!(bits_to_store(u16::MAX), 16);
+        assert_eq!(bits_to_store(1u64 << 40), 41);
+        assert_eq!([0u16, 3, 300].map(bits_to_store), [0, 2, 9]);
+    }
+
+    #[test]
+    fn test_min_fragment_width() {
+        assert_eq!(min_fragment_width(&[]), 0);
+        assert_eq!(min_fragment_width(&[0, 0, 0]), 0);
+        assert_eq!(min_fragment_width(&[0, 1, 0]), 1);
+        assert_eq!(min_fragment_width(&[4, 2, 7, 1]), 3);
+        assert_eq!(min_fragment_width(&[8, 2, 7, 1]), 4);
+        assert_eq!(min_fragment_width(&[3, u64::MAX, 0]), 64);
+        assert_eq!(min_fragment_width(&[1 << 63]), 64);
+    }
+
+    #[test]
+    fn test_assert_width_fits() {
+        assert_width_fits::<u8>(0);
+        assert_width_fits::<u16>(16);
+        assert_width_fits::<u32>(17);
+        assert_width_fits::<u64>(64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_width_too_large() {
+        assert_width_fits::<u32>(33);
     }
 }
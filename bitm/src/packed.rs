@@ -0,0 +1,123 @@
+use dyn_size_of::GetSize;
+use super::{n_lowest_bits_0_64, BitAccess, BitVec};
+
+/// Array of `BITS`-bit (in range [1, 64]) values, stored as consecutive fragments of a bit vector.
+///
+/// Unlike [`BitAccess::get_fragment`] and [`BitAccess::set_fragment`], which get the fragment size as an argument,
+/// the size is known at compile time, so the shifts and masks used to access the values are constant.
+pub struct PackedArray<const BITS: u8> {
+    /// Values, stored as fragments of `BITS` bits.
+    bits: Box<[u64]>,
+    /// Number of values.
+    len: usize
+}
+
+impl<const BITS: u8> GetSize for PackedArray<BITS> {
+    fn size_bytes_dyn(&self) -> usize { self.bits.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl<const BITS: u8> PackedArray<BITS> {
+    /// Fails to compile if `BITS` is out of range [1, 64].
+    const VALID_BITS: () = assert!(BITS >= 1 && BITS <= 64, "PackedArray: BITS must be in range [1, 64]");
+
+    /// The largest value that can be stored.
+    pub const MAX_VALUE: u64 = n_lowest_bits_0_64(BITS);
+
+    /// Returns array of `len` values, all initialized to zero.
+    pub fn with_zeroed(len: usize) -> Self {
+        let () = Self::VALID_BITS;
+        Self { bits: Box::with_zeroed_bits(len * BITS as usize), len }
+    }
+
+    /// Returns array of consecutive `values`. Each value must fit in `BITS` bits.
+    pub fn with_values<I>(values: I) -> Self where I: IntoIterator<Item=u64>, I::IntoIter: ExactSizeIterator {
+        let () = Self::VALID_BITS;
+        let values = values.into_iter();
+        let len = values.len();
+        Self { bits: Box::with_bit_fragments(values, len, BITS), len }
+    }
+
+    /// Returns the number of values.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether there are no values.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the bit vector that stores the values.
+    #[inline] pub fn as_bits(&self) -> &[u64] { &self.bits }
+
+    /// Returns the bit vector that stores the values.
+    #[inline] pub fn into_bits(self) -> Box<[u64]> { self.bits }
+
+    /// Returns the `index`-th value. Panics if `index` is out of bounds.
+    #[inline] pub fn get(&self, index: usize) -> u64 {
+        assert!(index < self.len, "PackedArray index {index} out of bounds");
+        unsafe { self.get_unchecked(index) }
+    }
+
+    /// Returns the `index`-th value, or [`None`] if `index` is out of bounds.
+    #[inline] pub fn try_get(&self, index: usize) -> Option<u64> {
+        (index < self.len).then(|| unsafe { self.get_unchecked(index) })
+    }
+
+    /// Returns the `index`-th value, without bounds checking.
+    #[inline(always)] unsafe fn get_unchecked(&self, index: usize) -> u64 {
+        self.bits.get_bits_unchecked(index * BITS as usize, BITS)
+    }
+
+    /// Sets the `index`-th value to `value`.
+    /// Panics if `index` is out of bounds or `value` does not fit in `BITS` bits.
+    #[inline] pub fn set(&mut self, index: usize, value: u64) {
+        assert!(index < self.len, "PackedArray index {index} out of bounds");
+        assert!(value <= Self::MAX_VALUE, "PackedArray value {value} does not fit in {BITS} bits");
+        unsafe { self.bits.set_bits_unchecked(index * BITS as usize, value, BITS) }
+    }
+
+    /// Returns iterator over the values.
+    #[inline] pub fn iter(&self) -> impl ExactSizeIterator<Item=u64> + '_ {
+        (0..self.len).map(|index| unsafe { self.get_unchecked(index) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn agrees_with_fragments<const BITS: u8>() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = || { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x };  // xorshift
+        let len = 300;
+        let mut array = PackedArray::<BITS>::with_zeroed(len);
+        let mut fragments = Box::<[u64]>::with_zeroed_bits(len * BITS as usize);
+        for _ in 0..3 * len {
+            let (index, value) = (next() as usize % len, next() & PackedArray::<BITS>::MAX_VALUE);
+            array.set(index, value);
+            fragments.set_fragment(index, value, BITS);
+        }
+        assert_eq!(array.as_bits(), &fragments[..], "BITS={BITS}");
+        for i in 0..len { assert_eq!(array.get(i), fragments.get_fragment(i, BITS), "BITS={BITS}, i={i}"); }
+        assert_eq!(array.try_get(len), None);
+        let copy = PackedArray::<BITS>::with_values(array.iter());
+        assert_eq!(copy.as_bits(), array.as_bits());
+        assert_eq!(copy.size_bytes_dyn(), fragments.size_bytes_dyn());
+    }
+
+    #[test]
+    fn packed_array() {
+        agrees_with_fragments::<1>();
+        agrees_with_fragments::<3>();
+        agrees_with_fragments::<7>();
+        agrees_with_fragments::<13>();
+        agrees_with_fragments::<32>();
+        agrees_with_fragments::<63>();
+        agrees_with_fragments::<64>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn value_too_large() {
+        PackedArray::<4>::with_zeroed(10).set(3, 16);
+    }
+}
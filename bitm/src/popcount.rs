@@ -0,0 +1,154 @@
+//! Counting ones in slices of 64-bit words.
+//!
+//! With the `simd` feature enabled, large slices are processed by AVX2 (on x86-64, if supported by the CPU)
+//! or NEON (on AArch64) code. Otherwise, or for other targets, the scalar loop is used.
+
+/// Returns the number of ones (set bits) in `words`, using scalar [`u64::count_ones`].
+#[inline] pub(crate) fn count_ones_scalar(words: &[u64]) -> usize {
+    words.iter().map(|s| s.count_ones() as usize).sum()
+}
+
+/// Returns the number of ones (set bits) in `words`.
+#[cfg(not(feature = "simd"))]
+#[inline] pub(crate) fn count_ones(words: &[u64]) -> usize {
+    count_ones_scalar(words)
+}
+
+/// Returns the number of ones (set bits) in `words`.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[inline] pub(crate) fn count_ones(words: &[u64]) -> usize {
+    if words.len() >= avx2::WORDS_PER_BLOCK && std::arch::is_x86_feature_detected!("avx2") {
+        unsafe { avx2::count_ones(words) }
+    } else {
+        count_ones_scalar(words)
+    }
+}
+
+/// Returns the number of ones (set bits) in `words`.
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+#[inline] pub(crate) fn count_ones(words: &[u64]) -> usize {
+    neon::count_ones(words)
+}
+
+/// Returns the number of ones (set bits) in `words`.
+#[cfg(all(feature = "simd", not(any(target_arch = "x86_64", target_arch = "aarch64"))))]
+#[inline] pub(crate) fn count_ones(words: &[u64]) -> usize {
+    count_ones_scalar(words)
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    /// Number of 256-bit vectors processed in each iteration of the Harley-Seal loop.
+    const VECTORS_PER_BLOCK: usize = 16;
+
+    /// Number of 64-bit words processed in each iteration of the Harley-Seal loop.
+    pub(super) const WORDS_PER_BLOCK: usize = VECTORS_PER_BLOCK * 4;
+
+    /// Carry-save adder: returns the high and low bits of the (per bit) sum `a + b + c`.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn csa(a: __m256i, b: __m256i, c: __m256i) -> (__m256i, __m256i) {
+        let u = _mm256_xor_si256(a, b);
+        (_mm256_or_si256(_mm256_and_si256(a, b), _mm256_and_si256(u, c)), _mm256_xor_si256(u, c))
+    }
+
+    /// Returns the numbers of ones in each of 4 64-bit lanes of `v`.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn count_lanes(v: __m256i) -> __m256i {
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4);
+        let low_mask = _mm256_set1_epi8(0x0f);
+        let lo = _mm256_and_si256(v, low_mask);
+        let hi = _mm256_and_si256(_mm256_srli_epi16(v, 4), low_mask);
+        let counts = _mm256_add_epi8(_mm256_shuffle_epi8(lookup, lo), _mm256_shuffle_epi8(lookup, hi));
+        _mm256_sad_epu8(counts, _mm256_setzero_si256())
+    }
+
+    /// Returns the number of ones (set bits) in `words`, using Harley-Seal algorithm.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_ones(words: &[u64]) -> usize {
+        let blocks = words.chunks_exact(WORDS_PER_BLOCK);
+        let tail = blocks.remainder();
+        let zero = _mm256_setzero_si256();
+        let (mut total, mut ones, mut twos, mut fours, mut eights) = (zero, zero, zero, zero, zero);
+        let (mut twos_a, mut twos_b, mut fours_a, mut fours_b, mut eights_a, mut eights_b, mut sixteens);
+        for block in blocks {
+            let p = block.as_ptr() as *const __m256i;
+            let v = |i: usize| unsafe { _mm256_loadu_si256(p.add(i)) };
+            (twos_a, ones) = csa(ones, v(0), v(1));
+            (twos_b, ones) = csa(ones, v(2), v(3));
+            (fours_a, twos) = csa(twos, twos_a, twos_b);
+            (twos_a, ones) = csa(ones, v(4), v(5));
+            (twos_b, ones) = csa(ones, v(6), v(7));
+            (fours_b, twos) = csa(twos, twos_a, twos_b);
+            (eights_a, fours) = csa(fours, fours_a, fours_b);
+            (twos_a, ones) = csa(ones, v(8), v(9));
+            (twos_b, ones) = csa(ones, v(10), v(11));
+            (fours_a, twos) = csa(twos, twos_a, twos_b);
+            (twos_a, ones) = csa(ones, v(12), v(13));
+            (twos_b, ones) = csa(ones, v(14), v(15));
+            (fours_b, twos) = csa(twos, twos_a, twos_b);
+            (eights_b, fours) = csa(fours, fours_a, fours_b);
+            (sixteens, eights) = csa(eights, eights_a, eights_b);
+            total = _mm256_add_epi64(total, count_lanes(sixteens));
+        }
+        total = _mm256_slli_epi64(total, 4);
+        total = _mm256_add_epi64(total, _mm256_slli_epi64(count_lanes(eights), 3));
+        total = _mm256_add_epi64(total, _mm256_slli_epi64(count_lanes(fours), 2));
+        total = _mm256_add_epi64(total, _mm256_slli_epi64(count_lanes(twos), 1));
+        total = _mm256_add_epi64(total, count_lanes(ones));
+        let lanes: [u64; 4] = std::mem::transmute(total);
+        lanes.iter().sum::<u64>() as usize + super::count_ones_scalar(tail)
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon {
+    use std::arch::aarch64::*;
+
+    /// Number of 64-bit words processed in each iteration.
+    const WORDS_PER_BLOCK: usize = 8;
+
+    /// Returns the number of ones (set bits) in `words`.
+    pub(super) fn count_ones(words: &[u64]) -> usize {
+        let blocks = words.chunks_exact(WORDS_PER_BLOCK);
+        let tail = blocks.remainder();
+        let mut total = 0;
+        for block in blocks {
+            let p = block.as_ptr();
+            // each byte of counts is at most 4*8=32, so it does not overflow
+            let counts = unsafe {
+                let c = |i: usize| vcntq_u8(vreinterpretq_u8_u64(vld1q_u64(p.add(i))));
+                vaddq_u8(vaddq_u8(c(0), c(2)), vaddq_u8(c(4), c(6)))
+            };
+            total += unsafe { vaddlvq_u8(counts) } as usize;
+        }
+        total + super::count_ones_scalar(tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn simd_agrees_with_scalar() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let mut words = Vec::new();
+        for len in [0, 1, 3, 4, 7, 8, 9, 15, 16, 17, 63, 64, 65, 100, 127, 128, 129, 200, 1000, 1027] {
+            words.clear();
+            for _ in 0..len {
+                x ^= x << 13; x ^= x >> 7; x ^= x << 17;    // xorshift
+                words.push(x);
+            }
+            assert_eq!(count_ones(&words), count_ones_scalar(&words), "len={len}");
+            words.fill(u64::MAX);
+            assert_eq!(count_ones(&words), len * 64, "len={len}");
+        }
+    }
+}
@@ -364,7 +364,19 @@ impl<BV: Deref<Target = [u64]>> RankSimple<BV> {
         r + (self.content[word_idx] & n_lowest_bits(word_offset)).count_ones() as u32
     }
 
-    //pub fn select(&self, rank: u32) -> usize {}
+    /// Returns the position of the `rank`-th one (counting from 0) in `content` or [`None`] if there are no such many ones.
+    /// The block that contains the one is found by binary search over `ranks`, in *O(log n)* time.
+    pub fn try_select(&self, rank: usize) -> Option<usize> {
+        let block = self.ranks.partition_point(|r| *r as usize <= rank).checked_sub(1)?;
+        let mut rank = rank - self.ranks[block] as usize;
+        for word_idx in block * (512 / 64)..self.content.len().min((block + 1) * (512 / 64)) {
+            let word = self.content[word_idx];
+            let ones = word.count_ones() as usize;
+            if rank < ones { return Some(word_idx * 64 + select64(word, rank as u8) as usize); }
+            rank -= ones;
+        }
+        None
+    }
 }
 
 impl<BV: Deref<Target = [u64]>> AsRef<[u64]> for RankSimple<BV> {
@@ -381,11 +393,15 @@ impl<BV: Deref<Target = [u64]>> Rank for RankSimple<BV> {
     }
 }
 
+impl<BV: Deref<Target = [u64]>> Select for RankSimple<BV> {
+    #[inline] fn try_select(&self, rank: usize) -> Option<usize> {
+        Self::try_select(self, rank)
+    }
+}
+
 /// Alias for backward compatibility.
 pub type ArrayWithRankSimple = RankSimple;
 
-//impl Select for ArrayWithRankSimple {}
-
 #[cfg(test)]
 mod tests {
     use crate::BitAccess;
@@ -478,6 +494,29 @@ mod tests {
         test_array_with_rank::<ArrayWithRankSimple>();
     }*/
 
+    #[test]
+    fn rank_select_simple_random() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for len in [1, 7, 8, 9, 50, 64] {
+            let content: Box<[u64]> = (0..len).map(|i| {
+                x ^= x << 13; x ^= x >> 7; x ^= x << 17;    // xorshift
+                if i % 5 == 3 { 0 } else if i % 3 == 0 { x & (x >> 3) } else { x }
+            }).collect();
+            let (a, ones) = ArrayWithRankSimple::build(content.clone());
+            assert_eq!(ones as usize, content.count_bit_ones());
+            let mut rank = 0;
+            for index in 0..len * 64 {
+                assert_eq!(a.rank(index) as usize, rank, "rank({index}) should be {rank}");
+                if content.get_bit(index) {
+                    assert_eq!(a.try_select(rank), Some(index), "select({rank}) should be {index}");
+                    rank += 1;
+                }
+            }
+            assert_eq!(a.try_select(rank), None);
+        }
+        assert_eq!(ArrayWithRankSimple::build(vec![].into_boxed_slice()).0.try_select(0), None);
+    }
+
     fn test_big_array_with_rank<ArrayWithRank: From<Box<[u64]>> + AsRef<[u64]> + Rank + Select + Select0>() {
         let a: ArrayWithRank = vec![0b1101; 60].into_boxed_slice().into();
         assert_eq!(a.try_select0(488), Some(513));
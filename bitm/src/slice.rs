@@ -0,0 +1,121 @@
+use super::{BitAccess, BitOnesIterator};
+
+/// View of the range of bits of a bit vector, that can be queried without copying the range.
+///
+/// All indices taken and returned by the methods are relative to the beginning of the range.
+#[derive(Clone, Copy)]
+pub struct BitSlice<'a> {
+    /// The whole bit vector.
+    bits: &'a [u64],
+    /// Index of the first bit of the range in `bits`.
+    begin: usize,
+    /// Number of bits in the range.
+    len: usize
+}
+
+impl<'a> BitSlice<'a> {
+    /// Returns view of `len` bits of `bits`, beginning from the bit index `begin`.
+    /// Panics if the range is out of bounds.
+    pub fn new(bits: &'a [u64], begin: usize, len: usize) -> Self {
+        Self::try_new(bits, begin, len).expect("BitSlice range out of bounds")
+    }
+
+    /// Returns view of `len` bits of `bits`, beginning from the bit index `begin`,
+    /// or [`None`] if the range is out of bounds.
+    pub fn try_new(bits: &'a [u64], begin: usize, len: usize) -> Option<Self> {
+        (begin.checked_add(len)? <= bits.len() * 64).then_some(Self { bits, begin, len })
+    }
+
+    /// Returns the whole bit vector that contains the range.
+    #[inline] pub fn bits(&self) -> &'a [u64] { self.bits }
+
+    /// Returns index (in [`Self::bits`]) of the first bit of the range.
+    #[inline] pub fn begin(&self) -> usize { self.begin }
+
+    /// Returns the number of bits.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether `self` contains no bits.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns view of the bits `[begin, begin+len)` of `self`. Panics if the range is out of bounds.
+    pub fn slice(&self, begin: usize, len: usize) -> Self {
+        assert!(begin.checked_add(len).is_some_and(|end| end <= self.len), "BitSlice range out of bounds");
+        Self { bits: self.bits, begin: self.begin + begin, len }
+    }
+
+    /// Gets bit with given index `bit_nr`. Panics if `bit_nr` is out of bounds.
+    #[inline] pub fn get_bit(&self, bit_nr: usize) -> bool {
+        self.try_get_bit(bit_nr).expect("BitSlice index out of bounds")
+    }
+
+    /// Gets bit with given index `bit_nr`, or [`None`] if `bit_nr` is out of bounds.
+    #[inline] pub fn try_get_bit(&self, bit_nr: usize) -> Option<bool> {
+        (bit_nr < self.len).then(|| unsafe { self.bits.get_bit_unchecked(self.begin + bit_nr) })
+    }
+
+    /// Gets bits `[begin, begin+len)`. Panics if the range is out of bounds.
+    #[inline] pub fn get_bits(&self, begin: usize, len: u8) -> u64 {
+        self.try_get_bits(begin, len).expect("BitSlice bit range out of bounds")
+    }
+
+    /// Gets bits `[begin, begin+len)`, or [`None`] if the range is out of bounds.
+    #[inline] pub fn try_get_bits(&self, begin: usize, len: u8) -> Option<u64> {
+        (begin.checked_add(len as usize)? <= self.len).then(|| unsafe { self.bits.get_bits_unchecked(self.begin + begin, len) })
+    }
+
+    /// Returns the number of ones (set bits).
+    #[inline] pub fn count_bit_ones(&self) -> usize {
+        self.bits.count_ones_in_range(self.begin, self.begin + self.len)
+    }
+
+    /// Returns iterator over indices of ones (set bits).
+    pub fn bit_ones(&self) -> impl Iterator<Item = usize> + 'a {
+        let (first_segment, end) = (self.begin / 64, self.begin + self.len);
+        let segments = &self.bits[first_segment..end.div_ceil(64)];
+        let (offset, len) = (self.begin % 64, self.len);
+        BitOnesIterator::new(segments)
+            .skip_while(move |i| *i < offset)
+            .take_while(move |i| *i < offset + len)
+            .map(move |i| i - offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn window_in_middle_of_segment() {
+        let bits = [0b1011_0000u64 | (1 << 63), 0b1101, 1 << 2 | 1 << 10];
+        let s = BitSlice::new(&bits, 60, 71);
+        assert_eq!(s.len(), 71);
+        assert!(!s.get_bit(0));
+        assert!(s.get_bit(3));
+        assert!(s.get_bit(4) && !s.get_bit(5) && s.get_bit(6) && s.get_bit(7));
+        assert!(s.get_bit(70));
+        assert_eq!(s.try_get_bit(71), None);
+        assert_eq!(s.get_bits(0, 8), 0b1101_1000);
+        assert_eq!(s.get_bits(3, 64), bits.get_bits(63, 64));
+        assert_eq!(s.try_get_bits(8, 64), None);
+        assert_eq!(s.count_bit_ones(), 5);
+        assert_eq!(s.bit_ones().collect::<Vec<_>>(), [3, 4, 6, 7, 70]);
+        let sub = s.slice(4, 3);
+        assert_eq!(sub.begin(), 64);
+        assert_eq!(sub.bit_ones().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(sub.count_bit_ones(), 2);
+    }
+
+    #[test]
+    fn window_within_one_segment() {
+        let bits = [u64::MAX];
+        let s = BitSlice::new(&bits, 5, 3);
+        assert_eq!(s.bit_ones().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(s.get_bits(0, 3), 0b111);
+        let empty = BitSlice::new(&bits, 64, 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.bit_ones().next(), None);
+        assert!(BitSlice::try_new(&bits, 60, 5).is_none());
+    }
+}
@@ -120,8 +120,8 @@ pub fn benchmark_u8(conf: &super::Conf) {
     let text = conf.text();
     let frequencies = frequencies_u8(conf, &text);
 
-    let dec_constr_ns = conf.measure(|| Coding::from_frequencies_cloned(BitsPerFragment(1), &frequencies)).as_nanos();
-    let coding = Coding::from_frequencies_cloned(BitsPerFragment(1), &frequencies);
+    let dec_constr_ns = conf.measure(|| Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &frequencies)).as_nanos();
+    let coding = Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &frequencies);
     let enc_constr_ns = conf.measure(|| coding.codes_for_values_array()).as_nanos();
     let rev_enc_constr_ns = conf.measure(|| coding.reversed_codes_for_values_array()).as_nanos();
 
@@ -167,8 +167,8 @@ pub fn benchmark(conf: &super::Conf) {
     let text = conf.text();
     let frequencies = frequencies(conf, &text);
 
-    let dec_constr_ns = conf.measure(|| Coding::from_frequencies_cloned(BitsPerFragment(1), &frequencies)).as_nanos();
-    let coding = Coding::from_frequencies_cloned(BitsPerFragment(1), &frequencies);
+    let dec_constr_ns = conf.measure(|| Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &frequencies)).as_nanos();
+    let coding = Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &frequencies);
     let enc_constr_ns = conf.measure(|| coding.codes_for_values()).as_nanos();
     let rev_enc_constr_ns = conf.measure(|| coding.reversed_codes_for_values()).as_nanos();
 
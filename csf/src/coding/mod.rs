@@ -1,8 +1,11 @@
 //! Encoding of values of any type to/from a sequence of code words of fixed bit length.
 
 use std::io;
-pub use minimum_redundancy::DecodingResult;
+pub use minimum_redundancy::{DecodingResult, best_bits_per_fragment};
+use minimum_redundancy::Frequencies;
 use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::iter::FusedIterator;
 
 pub use minimum_redundancy;
@@ -11,6 +14,8 @@ mod mr;
 pub use mr::*;
 mod geom;
 pub use geom::*;
+mod rle;
+pub use rle::*;
 
 #[derive(Default, Copy, Clone)]
 pub struct U8Code {
@@ -106,6 +111,13 @@ pub trait Coding {
     /// Returns code of the value `to_encode`.
     fn code_of<'e, Q>(&self, encoder: &Self::Encoder<'e>, to_encode: &Q) -> Self::Codeword where Q: Borrow<Self::Value>;
 
+    /// Replaces the content of `out` with the codes of all `values`, obtained using the same `encoder`.
+    /// Allows for reusing `out` (and its capacity) to encode many batches of values.
+    fn code_of_batch<'e>(&self, encoder: &Self::Encoder<'e>, values: &[Self::Value], out: &mut Vec<Self::Codeword>) {
+        out.clear();
+        out.extend(values.iter().map(|v| self.code_of(encoder, v)));
+    }
+
     /// Returns the length (number of fragments) of code of the value `to_encode`.
     /// (this is the same value as `code(to_encode).fragments`, but `code_len` is faster for some encoders)
     #[inline(always)] fn len_of_encoded<'e, Q>(&self, encoder: &Self::Encoder<'e>, to_encode: &Q) -> u8 where Q: Borrow<Self::Value> {
@@ -178,7 +190,45 @@ pub trait BuildCoding<V> {
         where Iter: IntoIterator, Iter::Item: Borrow<<Self::Coding as Coding>::Value>;
 }
 
+/// Returns the map from each value of `values` to the number of its occurrences in `values`.
+pub fn count_frequencies<V: Hash + Eq + Copy>(values: &[V]) -> HashMap<V, u32> {
+    HashMap::with_occurrences_of(values)
+}
+
 // Returns `fragment_nr`-th `bits_per_fragment`-bits fragment of `bits`.
 /*#[inline(always)] pub fn get_u32_fragment(bits: u32, bits_per_fragment: u8, fragment_nr: u8) -> u32 {
     bits.checked_shr(bits_per_fragment as u32 * fragment_nr as u32).map_or(0, |v| v & ((1u32 << bits_per_fragment) - 1))
 }*/
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_code_of_batch() {
+        let values = [1u8, 2, 1, 3, 1, 4, 2, 1, 5];
+        let coding = BuildMinimumRedundancy{ bits_per_fragment: 1 }.build_from_iter(values.iter(), 0);
+        let encoder = coding.encoder();
+        let mut out = vec![coding.code_of(&encoder, &5u8)];  // should be replaced
+        coding.code_of_batch(&encoder, &values, &mut out);
+        assert_eq!(out.len(), values.len());
+        for (v, c) in values.iter().zip(out.iter()) {
+            let expected = coding.code_of(&encoder, v);
+            assert_eq!((c.content, c.len), (expected.content, expected.len));
+        }
+        coding.code_of_batch(&encoder, &values[..2], &mut out);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn test_count_frequencies() {
+        let frequencies = count_frequencies(&[1u8, 2, 1, 3, 1, 4, 2, 1, 5]);
+        assert_eq!(frequencies.len(), 5);
+        assert_eq!(frequencies[&1], 4);
+        assert_eq!(frequencies[&2], 2);
+        assert_eq!(frequencies[&3], 1);
+        assert_eq!(frequencies.get(&6), None);
+        assert!(count_frequencies::<u32>(&[]).is_empty());
+    }
+}
@@ -34,7 +34,7 @@ impl<Value: Hash + Eq + Clone> Coding for minimum_redundancy::Coding<Value, Bits
     type Codeword = U8Code;
 
     #[inline(always)] fn bits_per_fragment(&self) -> u8 {
-        self.degree.0
+        self.degree.get()
     }
 
     #[inline(always)] fn decoder(&self) -> Self::Decoder<'_> {
@@ -85,7 +85,7 @@ pub struct BuildMinimumRedundancy {
     pub bits_per_fragment: u8
 }
 
-impl<Value: Hash + Eq + Clone> BuildCoding<Value> for BuildMinimumRedundancy {
+impl<Value: Hash + Eq + Clone + Ord> BuildCoding<Value> for BuildMinimumRedundancy {
     type Coding = minimum_redundancy::Coding<Value>;
 
     fn name(&self) -> String {
@@ -101,7 +101,11 @@ impl<Value: Hash + Eq + Clone> BuildCoding<Value> for BuildMinimumRedundancy {
     {
         if bits_per_fragment == 0 { bits_per_fragment = self.bits_per_fragment; }
         let freq = HashMap::<Value, u32>::with_occurrences_of(iter);
-        if bits_per_fragment == 0 { bits_per_fragment = entropy_to_bpf(freq.entropy()-0.2) }
-        Self::Coding::from_frequencies(BitsPerFragment(bits_per_fragment), freq)
+        let bits_per_fragment = if bits_per_fragment == 0 {
+            BitsPerFragment::new_unchecked(entropy_to_bpf(freq.entropy()-0.2))
+        } else {
+            BitsPerFragment::new(bits_per_fragment).expect("BuildMinimumRedundancy: bits_per_fragment must be in range [0, 31]")
+        };
+        Self::Coding::from_frequencies(bits_per_fragment, freq)
     }
 }
@@ -0,0 +1,138 @@
+use std::borrow::Borrow;
+use bitm::{BitAccess, BitVec};
+use super::{BuildCoding, Coding, Decoder, DecodingResult};
+
+/// Run-length coding of sequences of values.
+///
+/// Each run of equal values is encoded as the code of the value (given by `value_coding`),
+/// followed by the code of the length of the run (given by `run_length_coding`).
+#[derive(Clone)]
+pub struct RleCoding<VC, LC> {
+    /// Coding of values.
+    pub value_coding: VC,
+    /// Coding of run lengths.
+    pub run_length_coding: LC
+}
+
+/// Returns runs of equal values in `column`, as pairs: value, run length.
+fn runs<V: PartialEq>(column: &[V]) -> Vec<(&V, u32)> {
+    let mut result = Vec::<(&V, u32)>::new();
+    for v in column {
+        match result.last_mut() {
+            Some((last, len)) if *last == v && *len != u32::MAX => *len += 1,
+            _ => result.push((v, 1))
+        }
+    }
+    result
+}
+
+impl<VC: Coding, LC: Coding<Value = u32>> RleCoding<VC, LC> {
+    /// Constructs [`RleCoding`] optimal for the given `column`,
+    /// building the coding of values by `value_coding` and the coding of run lengths by `run_length_coding`.
+    pub fn from_column<BV, BL>(column: &[VC::Value], value_coding: &BV, run_length_coding: &BL) -> Self
+        where VC::Value: PartialEq, BV: BuildCoding<VC::Value, Coding = VC>, BL: BuildCoding<u32, Coding = LC>
+    {
+        let runs = runs(column);
+        Self {
+            value_coding: value_coding.build_from_iter(runs.iter().map(|(v, _)| *v), 0),
+            run_length_coding: run_length_coding.build_from_iter(runs.iter().map(|(_, l)| l), 0)
+        }
+    }
+
+    /// Encodes the given `column` and returns the bit vector that contains concatenated codes of its runs.
+    pub fn encode(&self, column: &[VC::Value]) -> Box<[u64]> where VC::Value: PartialEq {
+        let (value_encoder, run_length_encoder) = (self.value_coding.encoder(), self.run_length_coding.encoder());
+        let (value_bpf, run_length_bpf) = (self.value_coding.bits_per_fragment(), self.run_length_coding.bits_per_fragment());
+        let codes: Vec<_> = runs(column).into_iter().map(|(v, l)|
+            (self.value_coding.code_of(&value_encoder, v), self.run_length_coding.code_of(&run_length_encoder, &l))
+        ).collect();
+        let bits_len = codes.iter().map(|(vc, lc)|
+            self.value_coding.len_of(*vc) as usize * value_bpf as usize + self.run_length_coding.len_of(*lc) as usize * run_length_bpf as usize
+        ).sum();
+        let mut result = Box::<[u64]>::with_zeroed_bits(bits_len);
+        let mut bit_nr = 0;
+        for (value_code, run_length_code) in codes {
+            for f in self.value_coding.fragments_of(value_code) { result.init_successive_bits(&mut bit_nr, f as u64, value_bpf); }
+            for f in self.run_length_coding.fragments_of(run_length_code) { result.init_successive_bits(&mut bit_nr, f as u64, run_length_bpf); }
+        }
+        result
+    }
+
+    /// Returns iterator over `len` values decoded from `encoded` bit vector returned by [`Self::encode`].
+    #[inline] pub fn decode<'c, 'e>(&'c self, encoded: &'e [u64], len: usize) -> RleDecoder<'c, 'e, VC, LC> {
+        RleDecoder { coding: self, encoded, bit_nr: 0, remaining: len, current: None }
+    }
+}
+
+/// Iterator over values decoded by [`RleCoding`] that expands runs.
+pub struct RleDecoder<'c, 'e, VC: Coding, LC> {
+    coding: &'c RleCoding<VC, LC>,
+    encoded: &'e [u64],
+    /// Index of the first not decoded bit of `encoded`.
+    bit_nr: usize,
+    /// Number of values to yield.
+    remaining: usize,
+    /// Current value and the number of its remaining copies to yield.
+    current: Option<(<VC::Decoder<'c> as Decoder>::Decoded, u32)>
+}
+
+impl<'c, 'e, VC: Coding, LC: Coding<Value = u32>> RleDecoder<'c, 'e, VC, LC> {
+    /// Decodes the next value using `decoder` that uses `bits_per_fragment` bits per fragment.
+    fn decode_next<D: Decoder>(&mut self, mut decoder: D, bits_per_fragment: u8) -> Option<D::Decoded> {
+        loop {
+            match decoder.consume_checked(self.encoded.try_get_bits(self.bit_nr, bits_per_fragment)? as u8) {
+                DecodingResult::Value(v) => { self.bit_nr += bits_per_fragment as usize; return Some(v); }
+                DecodingResult::Invalid => return None,
+                DecodingResult::Incomplete => self.bit_nr += bits_per_fragment as usize
+            }
+        }
+    }
+}
+
+impl<'c, 'e, VC: Coding, LC: Coding<Value = u32>> Iterator for RleDecoder<'c, 'e, VC, LC>
+    where <VC::Decoder<'c> as Decoder>::Decoded: Clone
+{
+    type Item = <VC::Decoder<'c> as Decoder>::Decoded;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        if self.current.as_ref().is_none_or(|(_, copies)| *copies == 0) {
+            let value = self.decode_next(self.coding.value_coding.decoder(), self.coding.value_coding.bits_per_fragment())?;
+            let run_length = *self.decode_next(self.coding.run_length_coding.decoder(), self.coding.run_length_coding.bits_per_fragment())?.borrow();
+            self.current = Some((value, run_length));
+        }
+        let (value, copies) = self.current.as_mut()?;
+        *copies -= 1;
+        self.remaining -= 1;
+        Some(value.clone())
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::coding::BuildMinimumRedundancy;
+
+    #[test]
+    fn test_rle_round_trip() {
+        let mut column = Vec::new();
+        for (v, len) in [(1u8, 300), (2, 50), (1, 700), (3, 1), (4, 200), (2, 1000), (5, 2), (1, 300)] {
+            column.extend(std::iter::repeat_n(v, len));
+        }
+        let builder = BuildMinimumRedundancy { bits_per_fragment: 2 };
+        let rle = RleCoding::<minimum_redundancy::Coding<u8>, minimum_redundancy::Coding<u32>>::from_column(&column, &builder, &builder);
+        let encoded = rle.encode(&column);
+        let decoded: Vec<u8> = rle.decode(&encoded, column.len()).cloned().collect();
+        assert_eq!(decoded, column);
+
+        let plain = builder.build_from_iter(column.iter(), 0);
+        let plain_encoder = plain.encoder();
+        let plain_bits = column.iter().map(|v| plain.len_of_encoded(&plain_encoder, v) as usize).sum::<usize>() * plain.bits_per_fragment() as usize;
+        assert!(encoded.len() * 64 < plain_bits);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use ph::{BuildDefaultSeededHasher, BuildSeededHasher, stats};
+use dyn_size_of::GetSize;
+
+use crate::coding::BuildMinimumRedundancy;
+use crate::fp::collision_solver::{CollisionSolverBuilder, IsLossless};
+use crate::fp::level_sizer::LevelSizer;
+use super::{CMap, CMapConf};
+
+/// Finger-printing based static function (immutable map) that maps hashable keys to byte strings.
+///
+/// Different byte strings are stored once, in a shared arena.
+/// The function is represented by a [`CMap`] that maps each key to the index of its string in the arena.
+pub struct BytesMap<S = BuildDefaultSeededHasher> {
+    /// Maps keys to indices of strings.
+    indices: CMap<minimum_redundancy::Coding<u32>, S>,
+    /// `i`-th string occupies `arena[offsets[i]..offsets[i+1]]`.
+    offsets: Box<[usize]>,
+    /// Concatenated, different strings.
+    arena: Box<[u8]>
+}
+
+impl<S> GetSize for BytesMap<S> {
+    fn size_bytes_dyn(&self) -> usize {
+        self.indices.size_bytes_dyn() + self.offsets.size_bytes_dyn() + self.arena.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl<S: BuildSeededHasher> BytesMap<S> {
+    /// Gets the byte string associated with the given key `k` and reports statistics to `access_stats`.
+    ///
+    /// If the `k` was not in the input collection given during construction,
+    /// either [`None`] or a string assigned to other key is returned.
+    pub fn get_stats<K: Hash + ?Sized, A: stats::AccessStatsCollector>(&self, k: &K, access_stats: &mut A) -> Option<&[u8]> {
+        let index = *self.indices.get_stats(k, access_stats)? as usize;
+        Some(&self.arena[self.offsets[index]..self.offsets[index+1]])
+    }
+
+    /// Gets the byte string associated with the given key `k`.
+    ///
+    /// If the `k` was not in the input collection given during construction,
+    /// either [`None`] or a string assigned to other key is returned.
+    #[inline] pub fn get<K: Hash + ?Sized>(&self, k: &K) -> Option<&[u8]> {
+        self.get_stats(k, &mut ())
+    }
+
+    /// Returns the arena that stores (once) each different byte string.
+    #[inline] pub fn arena(&self) -> &[u8] { &self.arena }
+
+    /// Builds [`BytesMap`] for given key-string `pairs`, using the build configuration `conf` and reporting statistics with `stats`.
+    pub fn from_pairs_with_conf<'a, K, I, LSC, CSB, BS>(pairs: I, conf: CMapConf<BuildMinimumRedundancy, LSC, CSB, S>, stats: &mut BS) -> Self
+        where K: Hash,
+              I: IntoIterator<Item = (K, &'a [u8])>,
+              LSC: LevelSizer,
+              CSB: CollisionSolverBuilder + IsLossless,
+              BS: stats::BuildStatsCollector
+    {
+        let mut string_indices = HashMap::<&'a [u8], u32>::new();
+        let mut offsets = vec![0];
+        let mut arena = Vec::new();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        for (k, s) in pairs {
+            let index = *string_indices.entry(s).or_insert_with(|| {
+                arena.extend_from_slice(s);
+                offsets.push(arena.len());
+                (offsets.len() - 2) as u32
+            });
+            keys.push(k);
+            values.push(index);
+        }
+        Self {
+            indices: CMap::from_slices_with_conf(&mut keys, &values, conf, stats),
+            offsets: offsets.into_boxed_slice(),
+            arena: arena.into_boxed_slice()
+        }
+    }
+}
+
+impl BytesMap {
+    /// Builds [`BytesMap`] for given key-string `pairs`, using the default configuration and reporting statistics with `stats`.
+    pub fn from_pairs<'a, K: Hash, I: IntoIterator<Item = (K, &'a [u8])>, BS: stats::BuildStatsCollector>(pairs: I, stats: &mut BS) -> Self {
+        Self::from_pairs_with_conf(pairs, Default::default(), stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_shared_strings() {
+        let map = BytesMap::from_pairs([
+            ('a', &b"apple"[..]), ('b', b"banana"), ('c', b"apple"), ('d', b""), ('e', b"banana"), ('f', b"cherry")
+        ], &mut ());
+        assert_eq!(map.arena().len(), "applebananacherry".len());
+        assert_eq!(map.get(&'a'), Some(&b"apple"[..]));
+        assert_eq!(map.get(&'b'), Some(&b"banana"[..]));
+        assert_eq!(map.get(&'c'), Some(&b"apple"[..]));
+        assert_eq!(map.get(&'d'), Some(&b""[..]));
+        assert_eq!(map.get(&'e'), Some(&b"banana"[..]));
+        assert_eq!(map.get(&'f'), Some(&b"cherry"[..]));
+        assert_eq!(map.get(&'a').unwrap().as_ptr(), map.get(&'c').unwrap().as_ptr());
+        assert_eq!(map.get(&'b').unwrap().as_ptr(), map.get(&'e').unwrap().as_ptr());
+    }
+}
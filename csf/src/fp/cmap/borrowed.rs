@@ -0,0 +1,155 @@
+use std::hash::Hash;
+use std::io;
+use bitm::{BinaryRankSearch, RankSelect101111};
+use ph::{BuildDefaultSeededHasher, BuildSeededHasher, stats};
+use dyn_size_of::GetSize;
+use crate::coding::{Coding, Decoder, SerializableCoding};
+use super::{CMap, get_from_levels};
+
+/// Bit vector (borrowed) of all levels of [`BorrowedCMap`], with rank support.
+type BorrowedArrayWithRank<'a> = RankSelect101111<BinaryRankSearch, BinaryRankSearch, &'a [u64]>;
+
+/// Read-only view of [`CMap`] serialized by [`CMap::to_segments`],
+/// that borrows its (large) arrays from the serialized data instead of copying them.
+///
+/// It allows querying a map stored in a memory-mapped file without loading it to the heap.
+/// Only the rank structure (of the size of about 3% of the levels), the level sizes and the value coding are owned.
+pub struct BorrowedCMap<'a, C, S = BuildDefaultSeededHasher> {
+    array: BorrowedArrayWithRank<'a>,
+    value_fragments: &'a [u64],
+    level_sizes: &'a [u64],
+    value_coding: C,
+    hash_builder: S
+}
+
+impl<'a, C: GetSize, S> GetSize for BorrowedCMap<'a, C, S> {
+    fn size_bytes_dyn(&self) -> usize {   // the content of the levels and value fragments are borrowed
+        let mut result = self.array.l2ranks.size_bytes_dyn() + self.value_coding.size_bytes_dyn();
+        #[cfg(target_pointer_width = "64")] { result += self.array.l1ranks.size_bytes_dyn(); }
+        result
+    }
+
+    const USES_DYN_MEM: bool = true;
+}
+
+/// Returns [`io::ErrorKind::InvalidData`] error with given `message`.
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("BorrowedCMap: {message}"))
+}
+
+/// Removes and returns `len` first segments of `segments`.
+fn take<'a>(segments: &mut &'a [u64], len: u64) -> io::Result<&'a [u64]> {
+    let len = usize::try_from(len).ok().filter(|len| *len <= segments.len())
+        .ok_or_else(|| invalid_data("unexpected end of data"))?;
+    let (result, rest) = segments.split_at(len);
+    *segments = rest;
+    Ok(result)
+}
+
+/// Removes and returns the first segment of `segments`.
+fn take_one(segments: &mut &[u64]) -> io::Result<u64> {
+    Ok(take(segments, 1)?[0])
+}
+
+impl<C: SerializableCoding, S> CMap<C, S> {
+    /// Returns `self` serialized to 64-bit segments (using `write_value` to write values),
+    /// that can be queried without copying by [`BorrowedCMap`].
+    ///
+    /// The segments hold: the number of levels, the sizes of the levels,
+    /// the number of bytes of the value coding and the coding itself (padded to whole segments),
+    /// the bit vector of all levels, the number of segments of the value fragments and the value fragments.
+    /// Note that the segments use the native byte order.
+    pub fn to_segments<F>(&self, write_value: F) -> io::Result<Vec<u64>>
+        where F: FnMut(&mut dyn io::Write, &C::Value) -> io::Result<()>
+    {
+        let mut coding = Vec::new();
+        self.value_coding.write(&mut coding, write_value)?;
+        let mut result = Vec::with_capacity(3 + self.level_sizes.len() + coding.len().div_ceil(8)
+            + self.array.content.len() + self.value_fragments.len());
+        result.push(self.level_sizes.len() as u64);
+        result.extend_from_slice(&self.level_sizes);
+        result.push(coding.len() as u64);
+        result.extend(coding.chunks(8).map(|bytes| {
+            let mut segment = [0u8; 8];
+            segment[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(segment)
+        }));
+        result.extend_from_slice(&self.array.content);
+        result.push(self.value_fragments.len() as u64);
+        result.extend_from_slice(&self.value_fragments);
+        Ok(result)
+    }
+}
+
+impl<'a, C: SerializableCoding, S: BuildSeededHasher> BorrowedCMap<'a, C, S> {
+    /// Returns view of the map serialized to `segments` by [`CMap::to_segments`],
+    /// using `read_value` to read values (hasher must be the same as used by serialized `CMap`).
+    ///
+    /// Builds the rank structure, which requires a single pass over the bit vector of the levels.
+    pub fn from_bytes_with_hasher<F>(mut segments: &'a [u64], read_value: F, hasher: S) -> io::Result<Self>
+        where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
+    {
+        let levels = take_one(&mut segments)?;
+        let level_sizes = take(&mut segments, levels)?;
+        let coding_bytes = take_one(&mut segments)?;
+        let coding: Vec<u8> = take(&mut segments, coding_bytes.div_ceil(8))?.iter()
+            .flat_map(|segment| segment.to_le_bytes()).take(coding_bytes as usize).collect();
+        let value_coding = C::read(&mut &coding[..], read_value)?;
+        let content_len = level_sizes.iter().try_fold(0u64, |sum, size| sum.checked_add(*size))
+            .ok_or_else(|| invalid_data("invalid level sizes"))?;
+        let (array, number_of_ones) = BorrowedArrayWithRank::build(take(&mut segments, content_len)?);
+        let value_fragments_len = take_one(&mut segments)?;
+        let value_fragments = take(&mut segments, value_fragments_len)?;
+        if value_fragments.len() < (number_of_ones * value_coding.bits_per_fragment() as usize).div_ceil(64) {
+            return Err(invalid_data("too few value fragments"));
+        }
+        Ok(Self { array, value_fragments, level_sizes, value_coding, hash_builder: hasher })
+    }
+}
+
+impl<'a, C: SerializableCoding> BorrowedCMap<'a, C> {
+    /// Returns view of the map serialized to `segments` by [`CMap::to_segments`], using `read_value` to read values.
+    /// Only `CMap`s that use default hasher can be viewed by this method.
+    pub fn from_bytes<F>(segments: &'a [u64], read_value: F) -> io::Result<Self>
+        where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
+    {
+        Self::from_bytes_with_hasher(segments, read_value, Default::default())
+    }
+}
+
+impl<'a, C: Coding, S: BuildSeededHasher> BorrowedCMap<'a, C, S> {
+    /// Gets the value associated with the given key `k` and reports statistics to `access_stats`.
+    #[inline] pub fn get_stats<K: Hash + ?Sized, A: stats::AccessStatsCollector>(&self, k: &K, access_stats: &mut A) -> Option<<<C as Coding>::Decoder<'_> as Decoder>::Decoded> {
+        get_from_levels(k, &self.hash_builder, self.level_sizes, &self.array, self.value_fragments, &self.value_coding, access_stats)
+    }
+
+    /// Gets the value associated with the given key `k`.
+    #[inline(always)]
+    pub fn get<K: Hash + ?Sized>(&self, k: &K) -> Option<<<C as Coding>::Decoder<'_> as Decoder>::Decoded> {
+        self.get_stats(k, &mut ())
+    }
+
+    /// Returns the coding used to compress values.
+    #[inline] pub fn value_coding(&self) -> &C { &self.value_coding }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use binout::{AsIs, Serializer};
+
+    #[test]
+    fn query_through_borrowed_view() {
+        let keys: Vec<u32> = (0..3000).collect();
+        let values: Vec<u16> = keys.iter().map(|k| [7, 1 << 10, 3, u16::MAX, 3][*k as usize % 5]).collect();
+        let fpmap = CMap::from_slices(&mut keys.clone(), &values, &mut ());
+        let segments = fpmap.to_segments(|b, v| AsIs::write(b, *v)).unwrap();
+        let borrowed = BorrowedCMap::<minimum_redundancy::Coding<u16>>::from_bytes(&segments, |b| AsIs::read(b)).unwrap();
+        assert!(segments.as_ptr_range().contains(&borrowed.array.content.as_ptr()));   // borrowed, not copied
+        assert!(segments.as_ptr_range().contains(&borrowed.value_fragments.as_ptr()));
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(borrowed.get(k), Some(v)); }
+        for k in 3000..4000u32 { assert_eq!(borrowed.get(&k), fpmap.get(&k)); }
+        assert!(BorrowedCMap::<minimum_redundancy::Coding<u16>>::from_bytes(&segments[..segments.len() - 1], |b| AsIs::read(b)).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use crate::fp::level_sizer::OptimalLevelSize;
 use ph::{BuildDefaultSeededHasher, BuildSeededHasher};
 use crate::fp::collision_solver::{CollisionSolverBuilder, LoMemAcceptEquals};
 use crate::coding::BuildMinimumRedundancy;
+use crate::fp::WithSeed;
 
 /// Configuration accepted by [`fp::CMap`](crate::fp::CMap) constructors.
 //#[derive(Default)]
@@ -134,3 +135,13 @@ impl<BC, LSC, CS: CollisionSolverBuilder, S: BuildSeededHasher> CMapConf<BC, LSC
         Self { coding, level_sizer: level_size_chooser, collision_solver, hash }
     }
 }
+
+impl<BC, LSC, CS: CollisionSolverBuilder, S: BuildSeededHasher> CMapConf<BC, LSC, CS, S> {
+    /// Returns the configuration that mixes `seed` into the hash functions of `self` (see [`WithSeed`]).
+    ///
+    /// Changing the seed changes which keys collide at each level, and so the level sizes and the content of the map,
+    /// but not the values it returns. Reading the map requires the same [`WithSeed`] hasher.
+    pub fn with_seed(self, seed: u64) -> CMapConf<BC, LSC, CS, WithSeed<S>> {
+        CMapConf { coding: self.coding, level_sizer: self.level_sizer, collision_solver: self.collision_solver, hash: WithSeed::new(self.hash, seed) }
+    }
+}
@@ -1,20 +1,26 @@
+use std::borrow::Borrow;
 use std::hash::Hash;
 use binout::{VByte, AsIs, Serializer};
 use minimum_redundancy::DecodingResult;
-use bitm::{BitAccess, BitVec, Rank};
+use bitm::{BitAccess, BitVec, Rank, RankSelect101111};
+use std::ops::Deref;
 use crate::fp::level_sizer::LevelSizer;
 
 use ph::utils::{ArrayWithRank, read_bits};
 use ph::{BuildDefaultSeededHasher, BuildSeededHasher, stats, utils};
-use std::collections::HashMap;
+use std::collections::{HashMap, hash_map::Entry};
 use std::io;
 
 mod conf;
 pub use conf::CMapConf;
 
+mod borrowed;
+pub use borrowed::BorrowedCMap;
+
 use crate::fp::collision_solver::{CollisionSolver, CollisionSolverBuilder, IsLossless};
+use crate::fp::WithSeed;
 
-use crate::fp::common::{encode_all, encode_all_from_map};
+use crate::fp::common::{encode_all, encode_all_from_map, prefault};
 use dyn_size_of::GetSize;
 use crate::coding::{Coding, Decoder, SerializableCoding, BuildCoding};
 
@@ -26,6 +32,9 @@ use crate::coding::{Coding, Decoder, SerializableCoding, BuildCoding};
 /// The expected time complexity is *O(c)* for evaluation and *O(|X|c)* for construction
 /// (not counting building the encoding dictionary),
 /// where *c* is the average codeword length (given in code fragments) of the values.
+///
+/// Note that the fragments of each codeword are stored at different levels, in slots pointed by hashes of the key,
+/// so the values cannot be decoded (and enumerated) without the keys.
 pub struct CMap<C, S = BuildDefaultSeededHasher> {
     array: ArrayWithRank,
     value_fragments: Box<[u64]>,    // BitVec
@@ -48,33 +57,78 @@ impl<C, S: BuildSeededHasher> CMap<C, S> {
     #[inline(always)] fn index<K: Hash + ?Sized>(&self, k: &K, level_nr: u64, size: usize) -> usize {
         utils::map64_to_64(self.hash_builder.hash_one(k, level_nr), size as u64) as usize
     }
+
+    /// Sequentially touches each memory page of the internal arrays of `self`, to fault them in.
+    /// This reduces latency of the first queries, for example after loading `self` from memory-mapped file.
+    pub fn prefault(&self) {
+        prefault(&self.level_sizes);
+        prefault(&self.array.content);
+        prefault(&self.array.l2ranks);
+        prefault(&self.value_fragments);
+    }
+
+    /// Returns the number of levels.
+    #[inline] pub fn levels(&self) -> usize { self.level_sizes.len() }
+
+    /// Returns the size of the given `level` in bits. Panics if `level` is not less than [`Self::levels`].
+    #[inline] pub fn level_size_bits(&self, level: usize) -> usize { (self.level_sizes[level] as usize) << 6 }
+}
+
+/// Gets the value associated with the given key `k` from the levels of sizes `level_sizes` (in 64-bit segments)
+/// stored in `array`, and reports statistics to `access_stats`. Used by both [`CMap`] and [`BorrowedCMap`].
+#[inline]
+fn get_from_levels<'c, K, C, S, A, SEL, SEL0, BV>(k: &K, hash_builder: &S, level_sizes: &[u64],
+    array: &RankSelect101111<SEL, SEL0, BV>, value_fragments: &[u64], value_coding: &'c C, access_stats: &mut A)
+    -> Option<<<C as Coding>::Decoder<'c> as Decoder>::Decoded>
+    where K: Hash + ?Sized, C: Coding, S: BuildSeededHasher, A: stats::AccessStatsCollector,
+          RankSelect101111<SEL, SEL0, BV>: Rank, BV: Deref<Target = [u64]>
+{
+    let mut result_decoder = value_coding.decoder();
+    let mut array_begin_index = 0usize;
+    let mut level = 0usize;
+    loop {
+        let level_size = (*level_sizes.get(level)? as usize) << 6usize;
+        let i = array_begin_index + utils::map64_to_64(hash_builder.hash_one(k, level as u64), level_size as u64) as usize;
+        if array.content.get_bit(i) {
+            match result_decoder.consume(value_fragments.get_fragment(array.rank(i), value_coding.bits_per_fragment()) as u8) {
+                DecodingResult::Value(v) => {
+                    access_stats.found_on_level(level);
+                    return Some(v)
+                },
+                DecodingResult::Invalid => {
+                    access_stats.fail_on_level(level);
+                    return None
+                },
+                DecodingResult::Incomplete => {}
+            }
+        }
+        array_begin_index += level_size;
+        level += 1;
+    }
 }
 
 impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
     /// Gets the value associated with the given key `k` and reports statistics to `access_stats`.
     pub fn get_stats<K: Hash + ?Sized, A: stats::AccessStatsCollector>(&self, k: &K, access_stats: &mut A) -> Option<<<C as Coding>::Decoder<'_> as Decoder>::Decoded> {
-        let mut result_decoder = self.value_coding.decoder();
+        get_from_levels(k, &self.hash_builder, &self.level_sizes, &self.array, &self.value_fragments, &self.value_coding, access_stats)
+    }
+
+    /// Returns whether some level of `self` claims (stores a fragment of the code of) the given key `k`,
+    /// without reading and decoding its value.
+    ///
+    /// It is `true` for all keys in the input key-value collection given during construction.
+    /// For other keys, it can return either `false` or `true` (false positive),
+    /// also in some cases where [`Self::get`] returns [`None`].
+    pub fn may_contain<K: Hash + ?Sized>(&self, k: &K) -> bool {
         let mut array_begin_index = 0usize;
-        let mut level = 0usize;
-        loop {
-            let level_size = (*self.level_sizes.get(level)? as usize) << 6usize;
-            let i = array_begin_index + self.index(k, level as u64, level_size);
-            if self.array.content.get_bit(i) {
-                match result_decoder.consume(self.value_fragments.get_fragment(self.array.rank(i), self.value_coding.bits_per_fragment()) as u8) {
-                    DecodingResult::Value(v) => {
-                        access_stats.found_on_level(level);
-                        return Some(v)
-                    },
-                    DecodingResult::Invalid => {
-                        access_stats.fail_on_level(level);
-                        return None
-                    },
-                    DecodingResult::Incomplete => {}
-                }
+        for (level, level_size) in self.level_sizes.iter().enumerate() {
+            let level_size = (*level_size as usize) << 6usize;
+            if self.array.content.get_bit(array_begin_index + self.index(k, level as u64, level_size)) {
+                return true;
             }
             array_begin_index += level_size;
-            level += 1;
         }
+        false
     }
 
     /// Gets the value associated with the given key `k`.
@@ -83,6 +137,65 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
         self.get_stats(k, &mut ())
     }
 
+    /// Returns the coding used to compress values.
+    #[inline] pub fn value_coding(&self) -> &C { &self.value_coding }
+
+    /// Returns the average length (in bits) of codewords actually stored for `num_keys` keys
+    /// (the number of keys in the input collection given during construction),
+    /// i.e. the total number of bits of stored value fragments divided by `num_keys`.
+    /// The map does not store the number of keys and it cannot be recovered from the stored fragments
+    /// (as they can be shared by many keys), so it must be given.
+    ///
+    /// Comparing it to the average codeword length expected by the value coding
+    /// shows how well the stored values match the frequencies assumed by the coding.
+    /// Note that the result can be below the expected length,
+    /// as colliding keys with equal fragments can share a single slot.
+    pub fn realized_avg_code_len(&self, num_keys: usize) -> f64 {
+        let stored_fragments = self.array.content.count_bit_ones();
+        (stored_fragments * self.value_coding.bits_per_fragment() as usize) as f64 / num_keys as f64
+    }
+
+    /// Returns iterator over the codeword fragments stored at the given `level`, in the order of their slots.
+    ///
+    /// The fragments are parts of encoded codewords, not decoded values.
+    /// Intended for debugging. Panics if `level` is not less than the number of levels.
+    pub fn level_fragments(&self, level: usize) -> impl Iterator<Item = u64> + '_ {
+        let begin = self.level_sizes[..level].iter().map(|s| (*s as usize) << 6).sum::<usize>();
+        let end = begin + ((self.level_sizes[level] as usize) << 6);
+        let bits_per_fragment = self.value_coding.bits_per_fragment();
+        let first = self.array.rank(begin);
+        (first..first + self.array.content.count_ones_in_range(begin, end)).map(move |i| self.value_fragments.get_fragment(i, bits_per_fragment))
+    }
+
+    /// Returns the map from each codeword fragment to the number of its occurrences among the fragments stored at all levels.
+    ///
+    /// Colliding keys with equal fragments share a single slot, so such fragments are counted once.
+    /// Intended for diagnostics of how efficiently the value coding is used.
+    pub fn codeword_histogram(&self) -> HashMap<u64, usize> {
+        let mut result = HashMap::new();
+        let stored_fragments = self.array.content.count_bit_ones();
+        for fragment in self.value_fragments.fragments(self.value_coding.bits_per_fragment(), stored_fragments) {
+            *result.entry(fragment).or_insert(0) += 1;
+        }
+        result
+    }
+
+    /// Partitions `keys` into `num_shards` buckets by the values (shard indices) associated with them,
+    /// so that the `i`-th returned vector contains (in the order of `keys`) the keys mapped to `i`.
+    ///
+    /// Panics if any key is not found or is mapped to a value not less than `num_shards`.
+    pub fn partition_keys<K: Hash + Clone>(&self, keys: &[K], num_shards: usize) -> Vec<Vec<K>>
+        where C::Value: Copy + Into<u64>
+    {
+        let mut result = vec![Vec::new(); num_shards];
+        for k in keys {
+            let shard = (*self.get(k).expect("partition_keys: key not found").borrow()).into() as usize;
+            assert!(shard < num_shards, "partition_keys: shard index {shard} is not less than num_shards={num_shards}");
+            result[shard].push(k.clone());
+        }
+        result
+    }
+
     /// Build `CMap` for given keys -> values map, where:
     /// - keys are given directly
     /// - values are encoded by `value_coding` and given in as values_fragments and corresponding values_fragments_sizes
@@ -105,7 +218,7 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
         let mut value_rev_indices: Box<[u8]> = values.iter().map(|c| value_coding.len_of(*c)-1).collect();
         let mut level_nr = 0u64;
         while input_size != 0 {
-            let level_size_segments = conf.level_sizer.size_segments_for_values(
+            let level_size_segments = conf.level_sizer.size_segments_for_level_values(level_nr as usize,
                 || values[0..input_size].iter().zip(value_rev_indices[0..input_size].iter()).map(|(c, ri)| value_coding.rev_fragment_of(*c, *ri) as u64),
                 input_size,
                 value_coding.bits_per_fragment());
@@ -180,6 +293,12 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
     }
 }
 
+/// Magic bytes that begin the output of [`CMap::write_with_header`].
+const CMAP_MAGIC: [u8; 4] = *b"FPCM";
+
+/// Version of the format written by [`CMap::write_with_header`].
+const CMAP_FORMAT_VERSION: u8 = 1;
+
 impl<C: SerializableCoding, S: BuildSeededHasher> CMap<C, S> {
 
     /// Returns number of bytes which `write` will write, assuming that each call to `write_value` writes `bytes_per_value` bytes.
@@ -217,6 +336,37 @@ impl<C: SerializableCoding, S: BuildSeededHasher> CMap<C, S> {
             hash_builder: hasher
         })
     }
+
+    /// Writes `self` to the `output` like [`Self::write`], but preceded by the header (magic bytes and format version),
+    /// which is validated by [`Self::read_with_header`].
+    ///
+    /// All multi-byte numbers are written in little-endian order, so the result can be read on a machine
+    /// with different endianness, provided that `write_value` is endian-independent as well.
+    pub fn write_with_header<F>(&self, output: &mut dyn io::Write, write_value: F) -> io::Result<()>
+        where F: FnMut(&mut dyn io::Write, &C::Value) -> io::Result<()>
+    {
+        output.write_all(&CMAP_MAGIC)?;
+        AsIs::write(output, CMAP_FORMAT_VERSION)?;
+        self.write(output, write_value)
+    }
+
+    /// Reads `Self` written by [`Self::write_with_header`], using `read_value` to read values
+    /// (hasher must be the same as used by written `CMap`).
+    /// Returns [`io::ErrorKind::InvalidData`] error if the header is invalid.
+    pub fn read_with_header_and_hasher<F>(input: &mut dyn io::Read, read_value: F, hasher: S) -> io::Result<Self>
+        where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
+    {
+        let mut magic = [0u8; CMAP_MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if magic != CMAP_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "CMap: invalid magic bytes"));
+        }
+        let version: u8 = AsIs::read(input)?;
+        if version != CMAP_FORMAT_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("CMap: unsupported format version {version}")));
+        }
+        Self::read_with_hasher(input, read_value, hasher)
+    }
 }
 
 impl<C: SerializableCoding> CMap<C> {
@@ -227,6 +377,15 @@ impl<C: SerializableCoding> CMap<C> {
     {
         Self::read_with_hasher(input, read_value, Default::default())
     }
+
+    /// Reads `Self` written by [`Self::write_with_header`], using `read_value` to read values.
+    /// Only `CMap`s that use default hasher can be read by this method.
+    /// Returns [`io::ErrorKind::InvalidData`] error if the header is invalid.
+    pub fn read_with_header<F>(input: &mut dyn io::Read, read_value: F) -> io::Result<Self>
+        where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
+    {
+        Self::read_with_header_and_hasher(input, read_value, Default::default())
+    }
 }
 
 impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
@@ -243,6 +402,23 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
         Self::with_fragments(keys, &mut encode_all(&value_coding, values), value_coding, conf, stats)
     }
 
+    /// Builds [`CMap`] for the given `keys` and corresponding `codewords` of values,
+    /// which are already encoded by `value_coding` (for example by [`encode_all`]).
+    ///
+    /// Skips the encoding step, so the same `codewords` can be reused to build many maps.
+    pub fn from_slices_encoded<K, LSC, CSB, BS, BC>(
+        keys: &mut [K], codewords: &[C::Codeword],
+        value_coding: C, conf: CMapConf<BC, LSC, CSB, S>,
+        stats: &mut BS
+    ) -> Self
+        where K: Hash,
+              LSC: LevelSizer,
+              CSB: CollisionSolverBuilder + IsLossless,
+              BS: stats::BuildStatsCollector
+    {
+        Self::with_fragments(keys, &mut codewords.to_vec(), value_coding, conf, stats)
+    }
+
     pub fn from_slices_with_conf<K, LSC, CSB, BS, BC>(
         keys: &mut [K], values: &[C::Value], conf: CMapConf<BC, LSC, CSB, S>, stats: &mut BS
     ) -> Self
@@ -280,13 +456,98 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
     }
 }
 
+/// Error returned by [`CMap::try_from_slices_checked`] when the same key is given with two different values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKeyError<K> {
+    /// The key given with two different values.
+    pub key: K
+}
+
+impl<K: std::fmt::Debug> std::fmt::Display for DuplicateKeyError<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key {:?} is given with two different values", self.key)
+    }
+}
+
+impl<K: std::fmt::Debug> std::error::Error for DuplicateKeyError<K> {}
+
+impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
+    /// Builds [`CMap`] for the given `keys` and corresponding `values`, like [`Self::from_slices_with_conf`],
+    /// but first checks whether any key is given more than once.
+    /// Duplicates of the same key-value pair are ignored,
+    /// while a key given with two different values results in [`DuplicateKeyError`] that points the key.
+    pub fn try_from_slices_checked<K, LSC, CSB, BS, BC>(
+        keys: &[K], values: &[C::Value], conf: CMapConf<BC, LSC, CSB, S>, stats: &mut BS
+    ) -> Result<Self, DuplicateKeyError<K>>
+        where K: Hash + Eq + Clone,
+              C::Value: PartialEq,
+              LSC: LevelSizer,
+              CSB: CollisionSolverBuilder + IsLossless,
+              BS: stats::BuildStatsCollector,
+              BC: BuildCoding<C::Value, Coding=C>
+    {
+        let mut value_index_of = HashMap::<&K, usize>::with_capacity(keys.len());
+        let mut unique_keys = Vec::with_capacity(keys.len());
+        for (index, (key, value)) in keys.iter().zip(values).enumerate() {
+            match value_index_of.entry(key) {
+                Entry::Occupied(e) => if values[*e.get()] != *value {
+                    return Err(DuplicateKeyError { key: key.clone() });
+                },
+                Entry::Vacant(e) => {
+                    e.insert(index);
+                    unique_keys.push(key.clone());
+                }
+            }
+        }
+        let value_indices: Vec<usize> = unique_keys.iter().map(|k| value_index_of[k]).collect();
+        let value_coding = conf.coding.build_from_iter(value_indices.iter().map(|i| &values[*i]), 0);
+        let mut codewords: Vec<_> = {
+            let encoder = value_coding.encoder();
+            value_indices.iter().map(|i| value_coding.code_of(&encoder, &values[*i])).collect()
+        };
+        Ok(Self::with_fragments(&mut unique_keys, &mut codewords, value_coding, conf, stats))
+    }
+}
+
+impl<C: Coding, S: BuildSeededHasher + Clone> CMap<C, WithSeed<S>> {
+    /// Builds [`CMap`] for each of the given `seeds` (mixed into `conf.hash` by [`WithSeed`])
+    /// and returns the one with the lowest number of levels, together with its seed.
+    /// Ties are resolved in favor of the seed given earlier.
+    /// Each build uses a copy of `keys`, as the construction reorders them.
+    ///
+    /// Panics if `seeds` is empty.
+    pub fn from_slices_with_seed_search<K, LSC, CSB, BS, BC>(
+        keys: &[K], values: &[C::Value], conf: CMapConf<BC, LSC, CSB, S>,
+        seeds: impl IntoIterator<Item = u64>, stats: &mut BS
+    ) -> (Self, u64)
+        where K: Hash + Clone,
+              LSC: LevelSizer + Clone,
+              CSB: CollisionSolverBuilder + IsLossless + Clone,
+              BS: stats::BuildStatsCollector,
+              BC: BuildCoding<C::Value, Coding=C> + Clone
+    {
+        let mut best: Option<(Self, u64)> = None;
+        for seed in seeds {
+            let conf = CMapConf {
+                coding: conf.coding.clone(), level_sizer: conf.level_sizer.clone(),
+                collision_solver: conf.collision_solver.clone(), hash: WithSeed::new(conf.hash.clone(), seed)
+            };
+            let map = Self::from_slices_with_conf(&mut keys.to_vec(), values, conf, stats);
+            if best.as_ref().is_none_or(|(b, _)| map.level_sizes.len() < b.level_sizes.len()) {
+                best = Some((map, seed));
+            }
+        }
+        best.expect("CMap::from_slices_with_seed_search requires at least one seed")
+    }
+}
+
 impl<C: Coding> CMap<C> {
     pub fn from_slices_with_coding<K: Hash, BS: stats::BuildStatsCollector>(keys: &mut [K], values: &[C::Value], value_coding: C, stats: &mut BS) -> Self {
         Self::from_slices_with_coding_conf(keys, values, value_coding, CMapConf::default(), stats)
     }
 }
 
-impl<V: Hash + Eq + Clone> CMap<minimum_redundancy::Coding<V>> {
+impl<V: Hash + Eq + Ord + Clone> CMap<minimum_redundancy::Coding<V>> {
     pub fn from_slices<K: Hash, BS: stats::BuildStatsCollector>(keys: &mut [K], values: &[V], stats: &mut BS) -> Self {
         Self::from_slices_with_conf(keys, values, Default::default(), stats)
     }
@@ -296,13 +557,13 @@ impl<V: Hash + Eq + Clone> CMap<minimum_redundancy::Coding<V>> {
     }
 }
 
-impl<K: Hash + Clone, V: Hash + Eq + Clone, H> From<&HashMap<K, V, H>> for CMap<minimum_redundancy::Coding<V>> {
+impl<K: Hash + Clone, V: Hash + Eq + Ord + Clone, H> From<&HashMap<K, V, H>> for CMap<minimum_redundancy::Coding<V>> {
     fn from(map: &HashMap<K, V, H>) -> Self {
         Self::from_map(map, &mut ())
     }
 }
 
-impl<K: Hash + Clone, V: Hash + Eq + Clone, H> From<HashMap<K, V, H>> for CMap<minimum_redundancy::Coding<V>> {
+impl<K: Hash + Clone, V: Hash + Eq + Ord + Clone, H> From<HashMap<K, V, H>> for CMap<minimum_redundancy::Coding<V>> {
     fn from(map: HashMap<K, V, H>) -> Self {
         Self::from_map(&map, &mut ())
     }
@@ -317,6 +578,7 @@ mod tests {
     use maplit::hashmap;
     use bitm::ceiling_div;
     use crate::coding::BuildMinimumRedundancy;
+    use crate::fp::{ProportionalLevelSize, ResizedLevel};
 
     fn test_read_write<C: SerializableCoding<Value=u8>>(fpmap: &CMap<C>) {
         let mut buff = Vec::new();
@@ -327,6 +589,55 @@ mod tests {
         assert_eq!(fpmap.level_sizes, read.level_sizes);
     }
 
+    #[test]
+    fn levels() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let fpmap = CMap::from_slices(&mut keys.clone(), &values, &mut ());
+        assert!(fpmap.levels() >= 1);
+        let sizes: Vec<usize> = (0..fpmap.levels()).map(|level| fpmap.level_size_bits(level)).collect();
+        assert!(sizes.windows(2).all(|w| w[0] >= w[1]), "{sizes:?}");
+        assert_eq!(sizes.iter().sum::<usize>(), fpmap.array.content.len() * 64);
+    }
+
+    #[test]
+    fn read_write_with_header() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u32> = keys.iter().map(|k| [7, 1 << 20, 3, u32::MAX][*k as usize % 4]).collect();
+        let fpmap = CMap::from_slices(&mut keys.clone(), &values, &mut ());
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        fpmap.write_with_header(&mut cursor, |b, v| AsIs::write(b, *v)).unwrap();
+        cursor.set_position(0);
+        let read = CMap::<minimum_redundancy::Coding<u32>>::read_with_header(&mut cursor, |b| AsIs::read(b)).unwrap();
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(read.get(k), Some(v)); }
+
+        let mut corrupted = cursor.into_inner();
+        corrupted[0] ^= 1;
+        let err = CMap::<minimum_redundancy::Coding<u32>>::read_with_header(&mut &corrupted[..], |b| AsIs::read(b)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        corrupted[0] ^= 1;
+        corrupted[CMAP_MAGIC.len()] = CMAP_FORMAT_VERSION + 1;
+        let err = CMap::<minimum_redundancy::Coding<u32>>::read_with_header(&mut &corrupted[..], |b| AsIs::read(b)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_from_slices_encoded() {
+        let keys: Vec<u32> = (0..2000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| [0, 1, 0, 2, 0, 3, 0, 1][*k as usize % 8] + (k % 97 == 0) as u8 * 10).collect();
+        let coding = || BuildMinimumRedundancy::default().build_from_iter(values.iter(), 0);
+        let codewords = encode_all(&coding(), &values);
+        let from_raw = CMap::from_slices_with_coding_conf(&mut keys.clone(), &values, coding(), CMapConf::default(), &mut ());
+        let encoded = CMap::from_slices_encoded(&mut keys.clone(), &codewords, coding(), CMapConf::default(), &mut ());
+        let again = CMap::from_slices_encoded(&mut keys.clone(), &codewords, coding(), CMapConf::default(), &mut ());
+        test_fpmap_invariants(&encoded);
+        assert_eq!(encoded.level_sizes, from_raw.level_sizes);
+        assert_eq!(again.level_sizes, encoded.level_sizes);
+        for k in 0..3000u32 { assert_eq!(encoded.get(&k), from_raw.get(&k)); }
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(again.get(k), Some(v)); }
+    }
+
     fn test_fpmap_invariants<C: Coding>(fpmap: &CMap<C>) {
         assert_eq!(fpmap.level_sizes.iter().map(|v|*v as usize).sum::<usize>(), fpmap.array.content.len());
         assert_eq!(
@@ -370,4 +681,153 @@ mod tests {
     fn with_hashmap_bpf2() {
         test_8pairs(CMapConf::bpf(2));
     }
+
+    #[test]
+    fn test_seed_search() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let default_levels = CMap::from_slices(&mut keys.clone(), &values, &mut ()).level_sizes.len();
+        let (fpmap, seed) = CMap::from_slices_with_seed_search(&keys, &values, CMapConf::default(), 0..8, &mut ());
+        assert!(seed < 8);
+        assert!(fpmap.level_sizes.len() <= default_levels);
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let serialized = |fpmap: &CMap<minimum_redundancy::Coding<u8>, WithSeed<BuildDefaultSeededHasher>>| {
+            let mut buff = Vec::new();
+            fpmap.write(&mut buff, |b, v| AsIs::write(b, *v)).unwrap();
+            buff
+        };
+        let fpmap = CMap::from_slices_with_conf(&mut keys.clone(), &values, CMapConf::default().with_seed(42), &mut ());
+        let again = CMap::from_slices_with_conf(&mut keys.clone(), &values, CMapConf::default().with_seed(42), &mut ());
+        assert_eq!(serialized(&fpmap), serialized(&again));
+        let other = CMap::from_slices_with_conf(&mut keys.clone(), &values, CMapConf::default().with_seed(43), &mut ());
+        assert_ne!(serialized(&fpmap), serialized(&other));
+        let read = CMap::<minimum_redundancy::Coding<u8>, _>::read_with_hasher(&mut &serialized(&fpmap)[..],
+            |b| AsIs::read(b), WithSeed::new(BuildDefaultSeededHasher::default(), 42)).unwrap();
+        for (k, v) in keys.iter().zip(values.iter()) {
+            assert_eq!(fpmap.get(k), Some(v));
+            assert_eq!(read.get(k), Some(v));
+        }
+    }
+
+    #[test]
+    fn test_partition_keys() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let fpmap = CMap::from_map(&keys.iter().map(|k| (*k, (k * 7 % 4) as u8)).collect::<HashMap<_, _>>(), &mut ());
+        let shards = fpmap.partition_keys(&keys, 4);
+        assert_eq!(shards.len(), 4);
+        assert_eq!(shards.iter().map(Vec::len).sum::<usize>(), keys.len());
+        for (shard, shard_keys) in shards.iter().enumerate() {
+            assert_eq!(shard_keys.len(), 250);
+            for k in shard_keys { assert_eq!(fpmap.get(k), Some(&(shard as u8))); }
+        }
+    }
+
+    #[test]
+    fn test_realized_avg_code_len() {
+        // with 4 bits per fragment and many different values, colliding keys rarely share slots
+        let keys: Vec<u32> = (0..20000).collect();
+        let map: HashMap<u32, u16> = keys.iter().map(|k| (*k, (k * k % 211) as u16)).collect();
+        let fpmap = CMap::from_map_with_conf(&map, CMapConf::bpf(4), &mut ());
+        let code_lengths = fpmap.value_coding.code_lengths();
+        let expected = map.values().map(|v| code_lengths[v] as f64 * 4.0).sum::<f64>() / keys.len() as f64;
+        let realized = fpmap.realized_avg_code_len(keys.len());
+        assert!(realized <= expected && realized > 0.9 * expected, "realized={realized} expected={expected}");
+    }
+
+    #[test]
+    fn test_level_fragments() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let fpmap = CMap::from_map(&keys.iter().map(|k| (*k, (k % 5) as u8)).collect::<HashMap<_, _>>(), &mut ());
+        let max_fragment = (1u64 << fpmap.value_coding.bits_per_fragment()) - 1;
+        assert!(fpmap.level_fragments(0).next().is_some());
+        let mut total = 0;
+        for level in 0..fpmap.level_sizes.len() {
+            for fragment in fpmap.level_fragments(level) {
+                assert!(fragment <= max_fragment);
+                total += 1;
+            }
+        }
+        assert_eq!(total, fpmap.array.content.count_bit_ones());
+    }
+
+    #[test]
+    fn test_codeword_histogram() {
+        let conf = || CMapConf::coding(BuildMinimumRedundancy { bits_per_fragment: 1 });
+        let fpmap = CMap::from_slices_with_conf(&mut [1u32, 2], &[7u8, 9], conf(), &mut ());
+        assert_eq!(fpmap.codeword_histogram(), hashmap!(0 => 1, 1 => 1));
+
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let fpmap = CMap::from_slices_with_conf(&mut keys.clone(), &values, conf(), &mut ());
+        let histogram = fpmap.codeword_histogram();
+        assert_eq!(histogram.keys().copied().max(), Some(1));
+        assert_eq!(histogram.values().sum::<usize>(), fpmap.array.content.count_bit_ones());
+        let mut from_levels = HashMap::new();
+        for level in 0..fpmap.levels() {
+            for fragment in fpmap.level_fragments(level) { *from_levels.entry(fragment).or_insert(0) += 1; }
+        }
+        assert_eq!(histogram, from_levels);
+    }
+
+    #[test]
+    fn test_level_size_above_100_percent() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let fpmap = CMap::from_slices_with_conf(&mut keys.clone(), &values,
+            CMapConf::lsize(ProportionalLevelSize::with_percent(150)), &mut ());
+        let default_levels = CMap::from_slices(&mut keys.clone(), &values, &mut ()).level_sizes.len();
+        assert!(fpmap.level_sizes.len() <= default_levels);
+        test_fpmap_invariants(&fpmap);
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    fn test_resized_proportional_level_size() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let fpmap = CMap::from_slices_with_conf(&mut keys.clone(), &values,
+            CMapConf::lsize(ResizedLevel::new(50, ProportionalLevelSize::with_percent(200))), &mut ());
+        assert_eq!(fpmap.level_sizes[0], ceiling_div(1000, 64) as u64);
+        test_fpmap_invariants(&fpmap);
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    fn test_may_contain() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let fpmap = CMap::from_map(&keys.iter().map(|k| (*k, (k % 5) as u8)).collect::<HashMap<_, _>>(), &mut ());
+        for k in &keys { assert!(fpmap.may_contain(k)); }
+        assert!((1000..11000u32).any(|k| !fpmap.may_contain(&k)));
+    }
+
+    #[test]
+    fn test_u64_values() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u64> = keys.iter().map(|k| [0, 1 << 33, (1 << 40) - 1, 1 << 40][*k as usize % 4]).collect();
+        let fpmap = CMap::from_slices(&mut keys.clone(), &values, &mut ());
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    fn test_try_from_slices_checked() {
+        let keys = [1u32, 2, 3, 2, 4, 1];
+        let fpmap = CMap::try_from_slices_checked(&keys, &[10u8, 20, 30, 20, 40, 10], CMapConf::default(), &mut ()).unwrap();
+        for (k, v) in [(1, 10), (2, 20), (3, 30), (4, 40)] { assert_eq!(fpmap.get(&k), Some(&v)); }
+        let error = CMap::try_from_slices_checked(&keys, &[10u8, 20, 30, 21, 40, 10], CMapConf::default(), &mut ()).err();
+        assert_eq!(error, Some(DuplicateKeyError { key: 2 }));
+    }
+
+    #[test]
+    fn test_prefault() {
+        let keys: Vec<u32> = (0..10000).collect();
+        let fpmap = CMap::from_map(&keys.iter().map(|k| (*k, (k % 7) as u8)).collect::<HashMap<_, _>>(), &mut ());
+        fpmap.prefault();
+        for k in keys { assert_eq!(fpmap.get(&k), Some(&((k % 7) as u8))); }
+    }
 }
\ No newline at end of file
@@ -197,6 +197,72 @@ impl CollisionSolverBuilder for AcceptEquals {
 
 impl IsLossless for AcceptEquals {}
 
+/// Collision solver that never leaves an index under collision, but assigns to it the minimum of the values added.
+pub struct MinCollisionSolver {
+    /// Minimal fragments assigned to indices (uses 1 byte / value).
+    values: Box<[u8]>,
+    /// Which indices have assigned values.
+    current_array: Box<[u64]>
+}
+
+impl MinCollisionSolver {
+    fn new(level_size_segments: usize, _bits_per_value: u8) -> Self {
+        Self {
+            values: vec![0u8; level_size_segments * 64].into_boxed_slice(),
+            current_array: Box::<[u64]>::with_zeroed_64bit_segments(level_size_segments)
+        }
+    }
+}
+
+impl CollisionSolver for MinCollisionSolver {
+    #[inline(always)] fn is_under_collision(&self, _index: usize) -> bool { false }
+
+    fn add_value(&mut self, index: usize, value: u8, _bits_per_value: u8) {
+        if !self.current_array.get_bit(index) { // empty:
+            self.current_array.set_bit(index);
+            self.values[index] = value;
+        } else if value < self.values[index] {
+            self.values[index] = value;
+        }
+    }
+
+    fn to_collision_array(self) -> Box<[u64]> {
+        self.current_array
+    }
+
+    fn construct_value_array(number_of_values: usize, bits_per_value: u8) -> Box<[u64]> {
+        Box::<[u64]>::with_filled_bits(number_of_values*bits_per_value as usize)
+    }
+
+    fn set_value(output: &mut [u64], index: usize, value: u8, bits_per_value: u8) {
+        let value = value as u64;
+        output.conditionally_change_fragment(|old| if value < old { Some(value) } else { None }, index, bits_per_value);
+    }
+
+    fn to_collision_and_values(self, bits_per_value: u8) -> (Box<[u64]>, Box<[u64]>, usize) {
+        let (values, len) = select_values_f(&self.current_array, |i| self.values[i] as u64, bits_per_value);
+        (self.current_array, values, len)
+    }
+}
+
+/// Lossy collision solver for approximate min-aggregation.
+///
+/// All keys are resolved at the first level they are processed by, even if they collide.
+/// The colliding keys share a single slot that stores the minimum of their values,
+/// so the value returned for a key is not greater than (and only for keys without collision equal to) its value.
+#[derive(Default, Copy, Clone)]
+pub struct MinCollision;
+
+impl CollisionSolverBuilder for MinCollision {
+    type CollisionSolver = MinCollisionSolver;
+
+    #[inline(always)] fn new(&self, level_size_segments: usize, bits_per_value: u8) -> Self::CollisionSolver {
+        Self::CollisionSolver::new(level_size_segments, bits_per_value)
+    }
+
+    #[inline(always)] fn is_lossless(&self) -> bool { false }
+}
+
 #[derive(Copy, Clone)]
 struct LimitedDifferenceCell {
     /// total difference of added values over minimal value
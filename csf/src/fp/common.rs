@@ -18,6 +18,18 @@ pub (crate) fn concatenate_values(values: &[Box<[u64]>], values_lens: &[usize],
     result
 }
 
+/// Assumed size of memory page, in bytes.
+const PAGE_SIZE: usize = 4096;
+
+/// Reads one byte per memory page of `slice`, to fault its pages in.
+pub (crate) fn prefault<T>(slice: &[T]) {
+    let bytes = std::mem::size_of_val(slice);
+    let begin = slice.as_ptr() as *const u8;
+    for offset in (0..bytes).step_by(PAGE_SIZE) {
+        unsafe { std::ptr::read_volatile(begin.add(offset)); }
+    }
+}
+
 // Returns `conf` if it is greater than `0`, or `max(1, available parallelism + conf)` otherwise.
 /*pub fn threads_count(conf: isize) -> NonZeroUsize {
     if conf > 0 {
@@ -52,8 +64,25 @@ where F: FnMut() -> T + Send + Clone, T: Send {
 pub fn encode_all<C: Coding>(value_coding: &C, values: &[C::Value]) -> Vec::<C::Codeword>
     //where V: Hash + Eq + Clone
 {
+    let mut result = Vec::with_capacity(values.len());
+    value_coding.code_of_batch(&value_coding.encoder(), values, &mut result);
+    result
+}
+
+/// Encodes all `values` using `value_coding` and returns the bit vector of their concatenated codewords
+/// together with the lengths (in bits) of the successive codewords.
+pub fn encode_into_bits<C: Coding>(value_coding: &C, values: &[C::Value]) -> (Box<[u64]>, Vec<u8>) {
     let encoder = value_coding.encoder();
-    values.iter().map(|v| value_coding.code_of(&encoder, v)).collect()
+    let bits_per_fragment = value_coding.bits_per_fragment();
+    let lengths: Vec<u8> = values.iter().map(|v| value_coding.len_of_encoded(&encoder, v) * bits_per_fragment).collect();
+    let mut result = Box::<[u64]>::with_zeroed_bits(lengths.iter().map(|l| *l as usize).sum());
+    let mut fragment_index = 0;
+    for v in values {
+        for f in value_coding.fragments_of_encoded(&encoder, v) {
+            result.init_successive_fragment(&mut fragment_index, f as u64, bits_per_fragment);
+        }
+    }
+    (result, lengths)
 }
 
 pub fn encode_all_from_map<C: Coding, K, H>(value_coding: &C, map: &HashMap<K, C::Value, H>) -> (Vec<K>, Vec::<C::Codeword>)
@@ -69,3 +98,38 @@ pub fn encode_all_from_map<C: Coding, K, H>(value_coding: &C, map: &HashMap<K, C
     (keys, values)
 }
 
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::coding::{BuildCoding, BuildMinimumRedundancy};
+    use minimum_redundancy::DecodingResult;
+
+    #[test]
+    fn test_encode_into_bits() {
+        let values = [1u8, 2, 1, 3, 1, 4, 2, 1, 5, 1, 1, 6];
+        for bits_per_fragment in 1..=3 {
+            let coding = BuildMinimumRedundancy{ bits_per_fragment }.build_from_iter(values.iter(), 0);
+            let (bits, lengths) = encode_into_bits(&coding, &values);
+            assert_eq!(lengths.len(), values.len());
+            let mut bit_nr = 0;
+            for (v, len) in values.iter().zip(lengths) {
+                let mut decoder = coding.decoder();
+                let mut consumed = 0;
+                let decoded = loop {
+                    let fragment = bits.get_bits(bit_nr + consumed, bits_per_fragment) as u32;
+                    consumed += bits_per_fragment as usize;
+                    match decoder.consume(fragment) {
+                        DecodingResult::Value(d) => break *d,
+                        DecodingResult::Invalid => panic!("invalid codeword"),
+                        DecodingResult::Incomplete => {}
+                    }
+                };
+                assert_eq!(decoded, *v);
+                assert_eq!(consumed, len as usize);
+                bit_nr += consumed;
+            }
+            assert_eq!(bits.len(), bit_nr.div_ceil(64));
+        }
+    }
+}
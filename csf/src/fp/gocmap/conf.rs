@@ -1,4 +1,4 @@
-use crate::fp::OptimalLevelSize;
+use crate::fp::{OptimalLevelSize, WithSeed};
 use ph::BuildDefaultSeededHasher;
 use ph::fmph::{GOConf, GroupSize, SeedSize, TwoToPowerBitsStatic};
 use crate::coding::BuildMinimumRedundancy;
@@ -91,3 +91,107 @@ impl<BC, LSC, GS: GroupSize, SS: SeedSize, S> GOCMapConf<BC, LSC, GS, SS, S> {
         Self { coding, goconf, level_sizer: level_size_chooser }
     }
 }
+
+impl<BC, LSC, GS: GroupSize, SS: SeedSize, S> GOCMapConf<BC, LSC, GS, SS, S> {
+    /// Returns the configuration that mixes `seed` into the hash functions of `self` (see [`WithSeed`]).
+    ///
+    /// Changing the seed changes the assignment of keys to groups and the positions probed for each group seed,
+    /// and so the seeds selected, the level sizes and the content of the map.
+    pub fn with_seed(self, seed: u64) -> GOCMapConf<BC, LSC, GS, SS, WithSeed<S>> {
+        GOCMapConf {
+            coding: self.coding,
+            goconf: GOConf {
+                hash_builder: WithSeed::new(self.goconf.hash_builder, seed),
+                bits_per_seed: self.goconf.bits_per_seed,
+                bits_per_group: self.goconf.bits_per_group
+            },
+            level_sizer: self.level_sizer
+        }
+    }
+}
+
+/// Error returned by [`GOCMapConf::validate`] that points the incorrect field of the configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfError {
+    /// Name of the incorrect field.
+    pub field: &'static str,
+    /// Incorrect value of the field (in bits).
+    pub value: u8,
+    /// Description of the allowed values.
+    pub allowed: &'static str
+}
+
+impl std::fmt::Display for ConfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {} is not allowed: {}", self.field, self.value, self.allowed)
+    }
+}
+
+impl std::error::Error for ConfError {}
+
+impl<BC, LSC, GS: GroupSize, SS: SeedSize, S> GOCMapConf<BC, LSC, GS, SS, S> {
+    /// Checks whether the sizes of seeds and groups are in the supported ranges.
+    /// Returns [`ConfError`] that points the first incorrect field otherwise.
+    pub fn validate(&self) -> Result<(), ConfError> {
+        let bits_per_seed: u8 = self.goconf.bits_per_seed.into();
+        if bits_per_seed > 16 {
+            return Err(ConfError { field: "bits_per_seed", value: bits_per_seed, allowed: "seed size must be in range [0, 16] bits" });
+        }
+        if let Err(allowed) = self.goconf.bits_per_seed.validate() {
+            return Err(ConfError { field: "bits_per_seed", value: bits_per_seed, allowed });
+        }
+        let bits_per_group: u8 = self.goconf.bits_per_group.into();
+        if !(1..=128).contains(&bits_per_group) {
+            return Err(ConfError { field: "bits_per_group", value: bits_per_group, allowed: "group size must be in range [1, 128] bits" });
+        }
+        if let Err(allowed) = self.goconf.bits_per_group.validate() {
+            return Err(ConfError { field: "bits_per_group", value: bits_per_group, allowed });
+        }
+        Ok(())
+    }
+
+    /// Panics with the message describing [`ConfError`] if `self` is incorrect.
+    pub(super) fn validate_or_panic(&self) {
+        if let Err(error) = self.validate() { panic!("invalid GOCMapConf: {error}") }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use ph::fmph::Bits;
+
+    fn conf<GS: GroupSize, SS: SeedSize>(bits_per_seed: SS, bits_per_group: GS) -> GOCMapConf<BuildMinimumRedundancy, OptimalLevelSize, GS, SS> {
+        GOCMapConf::groups(GOConf::hash_bps_bpg(BuildDefaultSeededHasher::default(), bits_per_seed, bits_per_group))
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(GOCMapConf::default().validate(), Ok(()));
+        assert_eq!(conf(Bits(16), Bits(1)).validate(), Ok(()));
+        assert_eq!(conf(Bits(0), Bits(63)).validate(), Ok(()));
+        assert_eq!(conf(TwoToPowerBitsStatic::<4>, TwoToPowerBitsStatic::<7>).validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_seed_size() {
+        let error = conf(Bits(17), Bits(8)).validate().unwrap_err();
+        assert_eq!((error.field, error.value), ("bits_per_seed", 17));
+        assert_eq!(conf(TwoToPowerBitsStatic::<5>, Bits(8)).validate().unwrap_err().field, "bits_per_seed");
+    }
+
+    #[test]
+    fn invalid_group_size() {
+        let error = conf(Bits(4), Bits(0)).validate().unwrap_err();
+        assert_eq!((error.field, error.value), ("bits_per_group", 0));
+        let error = conf(Bits(4), Bits(64)).validate().unwrap_err();
+        assert_eq!((error.field, error.value), ("bits_per_group", 64));
+        assert_eq!(error.to_string(), "bits_per_group = 64 is not allowed: group sizes grater than 63 are not supported by Bits");
+    }
+
+    #[test]
+    fn invalid_seed_and_group_sizes() {
+        assert_eq!(conf(Bits(20), Bits(0)).validate().unwrap_err().field, "bits_per_seed");
+    }
+}
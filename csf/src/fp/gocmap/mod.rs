@@ -9,9 +9,10 @@ use super::collision_solver::{CountPositiveCollisions, LoMemAcceptEqualsSolver};
 use super::common::{encode_all, encode_all_from_map};
 use std::collections::HashMap;
 use std::io;
+use rayon::prelude::*;
 
 mod conf;
-pub use conf::GOCMapConf;
+pub use conf::{GOCMapConf, ConfError};
 use ph::fmph::{goindexing::group_nr, GroupSize, SeedSize, TwoToPowerBitsStatic};
 pub use ph::fmph::GOConf;
 use dyn_size_of::GetSize;
@@ -46,6 +47,44 @@ impl<C: GetSize, GS: GroupSize, SS: SeedSize, S> GetSize for GOCMap<C, GS, SS, S
     const USES_DYN_MEM: bool = true;
 }
 
+/// Numbers of bytes occupied by the components of [`GOCMap`], as returned by [`GOCMap::size_breakdown`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    /// Bit vectors of all levels, together with their rank structure.
+    pub levels: usize,
+    /// Fragments of the values' codewords.
+    pub value_fragments: usize,
+    /// Seeds of the groups.
+    pub group_seeds: usize,
+    /// Sizes of the levels.
+    pub level_sizes: usize,
+    /// The coding table (dynamically allocated part).
+    pub value_coding: usize,
+    /// Size of the [`GOCMap`] structure itself (without dynamically allocated parts).
+    pub structure: usize
+}
+
+impl SizeBreakdown {
+    /// Returns the total number of bytes, equal to [`GetSize::size_bytes`] of the map.
+    pub fn total(&self) -> usize {
+        self.levels + self.value_fragments + self.group_seeds + self.level_sizes + self.value_coding + self.structure
+    }
+}
+
+impl<C: GetSize, GS: GroupSize, SS: SeedSize, S> GOCMap<C, GS, SS, S> {
+    /// Returns the numbers of bytes occupied by the components of `self`, which sum to [`GetSize::size_bytes`].
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown {
+            levels: self.array.size_bytes_dyn(),
+            value_fragments: self.value_fragments.size_bytes_dyn(),
+            group_seeds: self.group_seeds.size_bytes_dyn(),
+            level_sizes: self.level_size.size_bytes_dyn(),
+            value_coding: self.value_coding.size_bytes_dyn(),
+            structure: std::mem::size_of::<Self>()
+        }
+    }
+}
+
 impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS, SS, S> {
     /// Maps value of each key to code fragment, and adds the fragment to collision solver.
     fn consider_all<K, LSC, GetGroupSeed, CS, BC>(conf: &GOCMapConf<BC, LSC, GS, SS, S>, coding: &C,
@@ -112,6 +151,73 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
         self.get_stats(k, &mut ())
     }
 
+    /// Returns the number of levels.
+    #[inline] pub fn levels(&self) -> usize { self.level_size.len() }
+
+    /// Returns the coding used to compress values.
+    #[inline] pub fn value_coding(&self) -> &C { &self.value_coding }
+
+    /// Returns the size of the given `level` in bits (i.e. the number of its groups multiplied by the group size).
+    /// Panics if `level` is not less than [`Self::levels`].
+    #[inline] pub fn level_size_bits(&self, level: usize) -> usize { self.goconf.bits_per_group * self.level_size[level] }
+
+    /// Returns an estimate of the peak memory (in bytes) needed to build `GOCMap` for `num_keys` keys with the given `conf`,
+    /// including both the working buffers and the final structure, but not the keys and the coding.
+    ///
+    /// The estimate is intended to be an upper bound, also for the multi-threaded construction.
+    /// However, it assumes that the fragments occupy at most 8 bits and that the total size of all levels
+    /// is at most [`Self::ESTIMATED_LEVELS_TO_FIRST`] times the maximal size of the first level
+    /// (which is `num_keys` bits for the default level sizer).
+    /// This holds unless the codewords of values are long, so the estimate can be exceeded in some cases.
+    pub fn estimate_build_bytes<BC, LSC: LevelSizer>(num_keys: usize, conf: &GOCMapConf<BC, LSC, GS, SS, S>) -> usize {
+        let (first_groups, first_segments) = conf.goconf.bits_per_group.level_size_groups_segments(
+            conf.level_sizer.max_size_segments(num_keys).saturating_mul(64));
+        let (all_groups, all_segments) = (Self::ESTIMATED_LEVELS_TO_FIRST * first_groups, Self::ESTIMATED_LEVELS_TO_FIRST * first_segments);
+        let seeds = 1usize << conf.goconf.bits_per_seed.into();
+        let codewords = num_keys * (std::mem::size_of::<C::Codeword>() + 1);  // with indices of current fragments
+        let level_construction = first_segments * 8 * (2 + 8)   // collision solver: collided, current array and values
+            + seeds * (first_segments * 64 * 2 + first_groups);   // counting collisions for all seeds
+        let structure = all_segments * 8 * 2    // levels, before and after concatenation
+            + all_segments * 8 / 8  // rank structure (much less)
+            + all_segments * 8 * 8  // value fragments (at most one for each bit of levels)
+            + all_groups * std::mem::size_of::<SS::VecElement>() * 2  // group seeds, before and after concatenation
+            + all_groups * std::mem::size_of::<usize>()    // level sizes
+            + std::mem::size_of::<Self>();
+        codewords + level_construction + structure
+    }
+
+    /// Maximal ratio of the total size of all levels to the maximal size of the first level, assumed by [`Self::estimate_build_bytes`].
+    pub const ESTIMATED_LEVELS_TO_FIRST: usize = 4;
+
+    /// Returns the seeds (one per group) that maximize the numbers of fragments assigned to each group,
+    /// given the numbers of fragments assigned to each group with the successive seeds, starting from `0`.
+    fn best_seeds<BC, LSC>(conf: &GOCMapConf<BC, LSC, GS, SS, S>, level_size_groups: usize, mut counts_for_seeds: impl Iterator<Item = Box<[u8]>>) -> Box<[SS::VecElement]> {
+        let mut best_seeds = conf.goconf.bits_per_seed.new_zeroed_seed_vec(level_size_groups);
+        let mut best_counts = counts_for_seeds.next().unwrap();
+        for (new_seed, with_new_seed) in (1u16..).zip(counts_for_seeds) {
+            for group_index in 0..level_size_groups {
+                let new = with_new_seed[group_index];
+                let best = &mut best_counts[group_index];
+                if new > *best {
+                    *best = new;
+                    unsafe { conf.goconf.bits_per_seed.set_seed(&mut best_seeds, group_index, new_seed) };
+                }
+            }
+        }
+        best_seeds
+    }
+
+    /// Returns the best seeds for the groups of the level, trying the seeds one by one.
+    fn select_seeds<K, LSC, BC>(conf: &GOCMapConf<BC, LSC, GS, SS, S>, coding: &C,
+                                keys: &[K], values: &[C::Codeword], value_rev_indices: &[u8],
+                                level_size_groups: usize, level_nr: u64) -> Box<[SS::VecElement]>
+        where K: Hash
+    {
+        Self::best_seeds(conf, level_size_groups, (0..1u32 << conf.goconf.bits_per_seed.into()).map(|seed|
+            Self::count_collisions_in_groups(conf, coding, keys, values, value_rev_indices, level_size_groups, level_nr, seed as u16)
+        ))
+    }
+
     /// Build `GOCMap` for given keys -> values map, where:
     /// - keys are given directly
     /// - values are encoded by Minimum-Redundancy (value_coding) and given in as values_fragments and corresponding values_fragments_sizes
@@ -124,7 +230,19 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
               LSC: LevelSizer,
               BS: stats::BuildStatsCollector
     {
-        conf.goconf.validate();
+        Self::with_fragments_using(keys, values, value_coding, conf, stats, Self::select_seeds)
+    }
+
+    /// Build `GOCMap` like [`Self::with_fragments`], using `select_seeds` to choose the seeds of groups at each level.
+    fn with_fragments_using<K, LSC, BS, BC, SelectSeeds>(
+        keys: &mut [K], values: &mut [C::Codeword],
+        value_coding: C, conf: GOCMapConf<BC, LSC, GS, SS, S>, stats: &mut BS, select_seeds: SelectSeeds) -> Self
+        where K: Hash,
+              LSC: LevelSizer,
+              BS: stats::BuildStatsCollector,
+              SelectSeeds: Fn(&GOCMapConf<BC, LSC, GS, SS, S>, &C, &[K], &[C::Codeword], &[u8], usize, u64) -> Box<[SS::VecElement]>
+    {
+        conf.validate_or_panic();
         let mut level_size = Vec::<usize>::new();
         let mut arrays = Vec::<Box<[u64]>>::new();
         let mut group_seeds = Vec::<Box<[SS::VecElement]>>::new();
@@ -135,7 +253,7 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
             let in_keys = &keys[0..input_size];
             let in_values = &values[0..input_size];
             let in_value_rev_indices = &value_rev_indices[0..input_size];
-            let suggested_level_size_segments = conf.level_sizer.size_segments_for_values(
+            let suggested_level_size_segments = conf.level_sizer.size_segments_for_level_values(level_nr as usize,
                 || in_values.iter().zip(in_value_rev_indices.iter()).map(|(c, ri)| value_coding.rev_fragment_of(*c, *ri) as u64),
                 input_size,
                 value_coding.bits_per_fragment());
@@ -143,22 +261,7 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
             let (level_size_groups, level_size_segments) = conf.goconf.bits_per_group.level_size_groups_segments(suggested_level_size_segments * 64);
             //let seed = level_nr;
             stats.level(input_size, level_size_segments * 64);
-            let mut best_seeds = conf.goconf.bits_per_seed.new_zeroed_seed_vec(level_size_groups);
-            let mut best_counts = Self::count_collisions_in_groups(&conf, &value_coding, in_keys, in_values, in_value_rev_indices,
-                                                                   level_size_groups,
-                                                                   level_nr, 0);
-            for new_seed in 1u16..=((1u32 << conf.goconf.bits_per_seed.into())-1) as u16 {
-                let with_new_seed = Self::count_collisions_in_groups(&conf, &value_coding, in_keys, in_values, in_value_rev_indices,
-                                                                     level_size_groups, level_nr, new_seed);
-                for group_index in 0..level_size_groups {
-                    let new = with_new_seed[group_index];
-                    let best = &mut best_counts[group_index];
-                    if new > *best {
-                        *best = new;
-                        unsafe { conf.goconf.bits_per_seed.set_seed(&mut best_seeds, group_index, new_seed) };
-                    }
-                }
-            }
+            let best_seeds = select_seeds(&conf, &value_coding, in_keys, in_values, in_value_rev_indices, level_size_groups, level_nr);
             let mut collision_solver = LoMemAcceptEqualsSolver::new(level_size_segments, value_coding.bits_per_fragment());
             Self::consider_all(&conf, &value_coding, in_keys, in_values, in_value_rev_indices,
                                level_size_groups, level_nr,
@@ -229,6 +332,88 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
     }
 }
 
+impl<C, GS, SS, S> GOCMap<C, GS, SS, S>
+    where C: Coding + Sync, C::Codeword: Sync, GS: GroupSize + Sync, SS: SeedSize, S: BuildSeededHasher + Sync
+{
+    /// Returns the best seeds for the groups of the level, trying the seeds in parallel, using the default [rayon] thread pool.
+    /// The result is the same as the one of [`Self::select_seeds`].
+    fn select_seeds_mt<K, LSC, BC>(conf: &GOCMapConf<BC, LSC, GS, SS, S>, coding: &C,
+                                   keys: &[K], values: &[C::Codeword], value_rev_indices: &[u8],
+                                   level_size_groups: usize, level_nr: u64) -> Box<[SS::VecElement]>
+        where K: Hash + Sync, LSC: Sync, BC: Sync
+    {
+        // for each group: (best count, lowest seed with this count); ties are resolved to the lowest seed, like in select_seeds
+        let best = (0..1u32 << conf.goconf.bits_per_seed.into()).into_par_iter().fold(
+            || (vec![0u8; level_size_groups], vec![u16::MAX; level_size_groups]),
+            |(mut best_counts, mut best_seeds), seed| {
+                let seed = seed as u16;
+                let counts = Self::count_collisions_in_groups(conf, coding, keys, values, value_rev_indices, level_size_groups, level_nr, seed);
+                for group_index in 0..level_size_groups {
+                    let new = counts[group_index];
+                    if new > best_counts[group_index] || (new == best_counts[group_index] && seed < best_seeds[group_index]) {
+                        best_counts[group_index] = new;
+                        best_seeds[group_index] = seed;
+                    }
+                }
+                (best_counts, best_seeds)
+            }
+        ).reduce_with(|(mut counts_a, mut seeds_a), (counts_b, seeds_b)| {
+            for group_index in 0..level_size_groups {
+                if counts_b[group_index] > counts_a[group_index] ||
+                    (counts_b[group_index] == counts_a[group_index] && seeds_b[group_index] < seeds_a[group_index]) {
+                    counts_a[group_index] = counts_b[group_index];
+                    seeds_a[group_index] = seeds_b[group_index];
+                }
+            }
+            (counts_a, seeds_a)
+        }).unwrap();
+        let mut best_seeds = conf.goconf.bits_per_seed.new_zeroed_seed_vec(level_size_groups);
+        for (group_index, seed) in best.1.into_iter().enumerate() {
+            unsafe { conf.goconf.bits_per_seed.set_seed(&mut best_seeds, group_index, seed) };
+        }
+        best_seeds
+    }
+
+    /// Gets the values associated with the given `keys` and stores them in `out`,
+    /// so `out[i]` is set to the value of `keys[i]`, like by [`Self::get`].
+    /// The keys are queried in parallel, using the default [rayon] thread pool.
+    /// Panics if `keys` and `out` are of different lengths.
+    pub fn get_batch<'s, K>(&'s self, keys: &[K], out: &mut [Option<<<C as Coding>::Decoder<'s> as Decoder>::Decoded>])
+        where K: Hash + Sync, <<C as Coding>::Decoder<'s> as Decoder>::Decoded: Send
+    {
+        assert_eq!(keys.len(), out.len(), "get_batch: keys and out are of different lengths");
+        keys.par_iter().zip(out.par_iter_mut()).for_each(|(k, result)| *result = self.get(k));
+    }
+
+    /// Build `GOCMap` like [`Self::with_fragments`], but searches for the seeds of groups using multiple threads
+    /// (of the default [rayon] thread pool).
+    /// The result does not depend on the number of threads and is the same as the one of [`Self::with_fragments`].
+    pub fn with_fragments_mt<K, LSC, BS, BC>(
+        keys: &mut [K], values: &mut [C::Codeword],
+        value_coding: C, conf: GOCMapConf<BC, LSC, GS, SS, S>, stats: &mut BS) -> Self
+        where K: Hash + Sync,
+              LSC: LevelSizer + Sync,
+              BS: stats::BuildStatsCollector,
+              BC: Sync
+    {
+        Self::with_fragments_using(keys, values, value_coding, conf, stats, Self::select_seeds_mt)
+    }
+
+    /// Build `GOCMap` like [`Self::from_slices_with_coding_conf`], but using multiple threads (see [`Self::with_fragments_mt`]).
+    pub fn from_slices_with_coding_conf_mt<K, LSC, BS, BC>(
+        keys: &mut [K], values: &[C::Value],
+        value_coding: C, conf: GOCMapConf<BC, LSC, GS, SS, S>,
+        stats: &mut BS
+    ) -> Self
+        where K: Hash + Sync,
+              LSC: LevelSizer + Sync,
+              BS: stats::BuildStatsCollector,
+              BC: Sync
+    {
+        Self::with_fragments_mt(keys, &mut encode_all(&value_coding, values), value_coding, conf, stats)
+    }
+}
+
 impl<C: SerializableCoding, S: BuildSeededHasher, GS: GroupSize, SS: SeedSize> GOCMap<C, GS, SS, S> {
     /// Returns number of bytes which `write` will write, assuming that each call to `write_value` writes `bytes_per_value` bytes.
     pub fn write_bytes(&self, bytes_per_value: usize) -> usize {
@@ -315,6 +500,7 @@ impl<GS: GroupSize, SS: SeedSize, C: Coding, S: BuildSeededHasher> GOCMap<C, GS,
               BS: stats::BuildStatsCollector,
               BC: BuildCoding<C::Value, Coding=C>
     {
+        conf.validate_or_panic();
         Self::from_slices_with_coding_conf(keys, values, conf.coding.build_from_iter(values, 0), conf, stats)
     }
 
@@ -338,6 +524,7 @@ impl<GS: GroupSize, SS: SeedSize, C: Coding, S: BuildSeededHasher> GOCMap<C, GS,
               BS: stats::BuildStatsCollector,
               BC: BuildCoding<C::Value, Coding=C>
     {
+        conf.validate_or_panic();
         Self::from_map_with_coding_conf(map, conf.coding.build_from_iter(map.values(), 0), conf, stats)
     }
 }
@@ -348,7 +535,7 @@ impl<C: Coding> GOCMap<C> {
     }
 }
 
-impl<V: Hash + Eq + Clone> GOCMap<minimum_redundancy::Coding<V>> {
+impl<V: Hash + Eq + Ord + Clone> GOCMap<minimum_redundancy::Coding<V>> {
     pub fn from_slices<K: Hash, BS: stats::BuildStatsCollector>(keys: &mut [K], values: &[V], stats: &mut BS) -> Self {
         Self::from_slices_with_conf(keys, values, Default::default(), stats)
     }
@@ -358,13 +545,13 @@ impl<V: Hash + Eq + Clone> GOCMap<minimum_redundancy::Coding<V>> {
     }
 }
 
-impl<K: Hash + Clone, V: Hash + Eq + Clone, H> From<&HashMap<K, V, H>> for GOCMap<minimum_redundancy::Coding<V>> {
+impl<K: Hash + Clone, V: Hash + Eq + Ord + Clone, H> From<&HashMap<K, V, H>> for GOCMap<minimum_redundancy::Coding<V>> {
     fn from(map: &HashMap<K, V, H>) -> Self {
         Self::from_map(map, &mut ())
     }
 }
 
-impl<K: Hash + Clone, V: Hash + Eq + Clone, H> From<HashMap<K, V, H>> for GOCMap<minimum_redundancy::Coding<V>> {
+impl<K: Hash + Clone, V: Hash + Eq + Ord + Clone, H> From<HashMap<K, V, H>> for GOCMap<minimum_redundancy::Coding<V>> {
     fn from(map: HashMap<K, V, H>) -> Self {
         Self::from_map(&map, &mut ())
     }
@@ -379,6 +566,7 @@ mod tests {
     use bitm::ceiling_div;
     use maplit::hashmap;
     use crate::coding::BuildMinimumRedundancy;
+    use ph::fmph::Bits;
     //use minimum_redundancy::{write_int, read_int};
 
     fn test_read_write<GS: GroupSize, SS: SeedSize, C: SerializableCoding<Value=u8>>(fpmap: &GOCMap<C, GS, SS>) where SS::VecElement: PartialEq + Debug {
@@ -440,4 +628,108 @@ mod tests {
         test_8pairs(GOCMapConf::bpf(2));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn with_u64_values() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u64> = keys.iter().map(|k| [0, 1 << 33, (1 << 40) - 1, 1 << 40][*k as usize % 4]).collect();
+        let fpmap = GOCMap::from_slices(&mut keys.clone(), &values, &mut ());
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    fn with_seed() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let serialized = |seed| {
+            let mut buff = Vec::new();
+            GOCMap::from_slices_with_conf(&mut keys.clone(), &values, GOCMapConf::default().with_seed(seed), &mut ())
+                .write(&mut buff, |b, v| AsIs::write(b, *v)).unwrap();
+            buff
+        };
+        assert_eq!(serialized(7), serialized(7));
+        assert_ne!(serialized(7), serialized(8));
+        let fpmap = GOCMap::from_slices_with_conf(&mut keys.clone(), &values, GOCMapConf::default().with_seed(7), &mut ());
+        for (k, v) in keys.iter().zip(values.iter()) { assert_eq!(fpmap.get(k), Some(v)); }
+    }
+
+    #[test]
+    #[should_panic(expected = "bits_per_seed = 17")]
+    fn invalid_conf() {
+        let conf = GOCMapConf::groups(GOConf::hash_bps_bpg(BuildDefaultSeededHasher::default(), Bits(17), Bits(8)));
+        GOCMap::from_slices_with_conf(&mut [1u32, 2, 3], &[1u8, 2, 1], conf, &mut ());
+    }
+
+    #[test]
+    fn get_batch() {
+        let keys: Vec<u32> = (0..5000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k * 13 % 7) as u8).collect();
+        let fpmap = GOCMap::from_slices(&mut keys.clone(), &values, &mut ());
+        let mut x = 0x9E37_79B9u32;
+        let queries: Vec<u32> = (0..20000).map(|_| { x ^= x << 13; x ^= x >> 17; x ^= x << 5; x % 10000 }).collect();
+        let mut out = vec![None; queries.len()];
+        fpmap.get_batch(&queries, &mut out);
+        for (k, v) in queries.iter().zip(out.iter()) { assert_eq!(*v, fpmap.get(k)); }
+    }
+
+    #[test]
+    fn levels() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 5) as u8).collect();
+        let fpmap = GOCMap::from_slices(&mut keys.clone(), &values, &mut ());
+        assert!(fpmap.levels() >= 1);
+        let sizes: Vec<usize> = (0..fpmap.levels()).map(|level| fpmap.level_size_bits(level)).collect();
+        assert!(sizes.windows(2).all(|w| w[0] >= w[1]), "{sizes:?}");
+        assert!(sizes.iter().sum::<usize>() <= fpmap.array.content.len() * 64);
+    }
+
+    #[test]
+    fn size_breakdown() {
+        let keys: Vec<u32> = (0..5000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 7) as u8).collect();
+        let fpmap = GOCMap::from_slices(&mut keys.clone(), &values, &mut ());
+        let breakdown = fpmap.size_breakdown();
+        assert_eq!(breakdown.total(), fpmap.size_bytes());
+        assert!(breakdown.levels > 0 && breakdown.value_fragments > 0 && breakdown.group_seeds > 0);
+    }
+
+    #[test]
+    fn estimate_build_bytes() {
+        let conf = GOCMapConf::default();
+        let estimate = |num_keys| GOCMap::<minimum_redundancy::Coding<u8>>::estimate_build_bytes(num_keys, &conf);
+        assert!(estimate(0) < estimate(1000));
+        assert!(estimate(1000) < estimate(10_000));
+        assert!(estimate(10_000) < estimate(1_000_000));
+        assert!(estimate(1_000_000) < 1_000_000 * 1000);
+        let keys: Vec<u32> = (0..5000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 7) as u8).collect();
+        let fpmap = GOCMap::from_slices(&mut keys.clone(), &values, &mut ());
+        assert!(estimate(keys.len()) > fpmap.size_bytes() - fpmap.value_coding.size_bytes_dyn());
+        let first_level_bits = conf.level_sizer.max_size_segments(keys.len()) * 64;
+        for bits_per_fragment in [1, 2, 3] {
+            let fpmap = GOCMap::from_slices_with_conf(&mut keys.clone(), &values, GOCMapConf::bpf(bits_per_fragment), &mut ());
+            let levels_bits: usize = (0..fpmap.levels()).map(|level| fpmap.level_size_bits(level)).sum();
+            assert!(levels_bits <= GOCMap::<minimum_redundancy::Coding<u8>>::ESTIMATED_LEVELS_TO_FIRST * first_level_bits);
+        }
+    }
+
+    #[test]
+    fn mt_build_equals_st_build() {
+        let keys: Vec<u32> = (0..5000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| (k % 7) as u8).collect();
+        let conf = GOCMapConf::bpf(2);
+        let st = GOCMap::from_slices_with_coding_conf(&mut keys.clone(), &values,
+            conf.coding.build_from_iter(values.iter(), 0), conf.clone(), &mut ());
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let mt = pool.install(|| GOCMap::from_slices_with_coding_conf_mt(&mut keys.clone(), &values,
+            conf.coding.build_from_iter(values.iter(), 0), conf.clone(), &mut ()));
+        assert_eq!(st.level_size, mt.level_size);
+        assert_eq!(st.group_seeds, mt.group_seeds);
+        assert_eq!(st.array.content, mt.array.content);
+        assert_eq!(st.value_fragments, mt.value_fragments);
+        for (k, v) in keys.iter().zip(values.iter()) {
+            assert_eq!(st.get(k), Some(v));
+            assert_eq!(mt.get(k), Some(v));
+        }
+        test_fpcmap_invariants(&mt);
+    }
+}
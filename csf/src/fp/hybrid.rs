@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use ph::{BuildDefaultSeededHasher, BuildSeededHasher};
+
+use super::CMap;
+
+/// Map that combines a static [`CMap`] with a small, mutable overlay of overrides.
+///
+/// Queries check the overrides first and then the [`CMap`],
+/// so the overrides shadow the values stored in the [`CMap`] and can also add new keys.
+pub struct HybridMap<K, V, S = BuildDefaultSeededHasher> {
+    /// Static function that handles most keys.
+    pub csf: CMap<minimum_redundancy::Coding<V>, S>,
+    /// Exceptions and updates, checked before `csf`.
+    pub overrides: HashMap<K, V>
+}
+
+impl<K, V, S> From<CMap<minimum_redundancy::Coding<V>, S>> for HybridMap<K, V, S> {
+    #[inline] fn from(csf: CMap<minimum_redundancy::Coding<V>, S>) -> Self {
+        Self { csf, overrides: HashMap::new() }
+    }
+}
+
+impl<K: Hash + Eq, V: Hash + Eq + Clone, S: BuildSeededHasher> HybridMap<K, V, S> {
+    /// Gets the value associated with the given key `k`: the override if present, else the value from the [`CMap`].
+    ///
+    /// If the `k` has no override and was not in the input collection given during construction of the [`CMap`],
+    /// either [`None`] or a value assigned to other key is returned.
+    #[inline] pub fn get(&self, k: &K) -> Option<&V> {
+        self.overrides.get(k).or_else(|| self.csf.get(k))
+    }
+
+    /// Overrides the value associated with the key `k` by `v`. Returns the previous override of `k`, if any.
+    #[inline] pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.overrides.insert(k, v)
+    }
+
+    /// Removes the override of the key `k`, restoring its value from the [`CMap`]. Returns the removed override, if any.
+    #[inline] pub fn remove(&mut self, k: &K) -> Option<V> {
+        self.overrides.remove(k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use maplit::hashmap;
+
+    #[test]
+    fn test_overrides() {
+        let mut map: HybridMap<char, u8> = CMap::from_map(&hashmap!('a'=>1u8, 'b'=>2, 'c'=>1, 'd'=>3), &mut ()).into();
+        assert_eq!(map.get(&'b'), Some(&2));
+        assert_eq!(map.insert('b', 7), None);
+        assert_eq!(map.insert('e', 5), None);
+        assert_eq!(map.get(&'b'), Some(&7));
+        assert_eq!(map.get(&'e'), Some(&5));
+        assert_eq!(map.get(&'a'), Some(&1));
+        assert_eq!(map.insert('b', 8), Some(7));
+        assert_eq!(map.get(&'b'), Some(&8));
+        assert_eq!(map.remove(&'b'), Some(8));
+        assert_eq!(map.get(&'b'), Some(&2));
+        assert_eq!(map.remove(&'b'), None);
+    }
+}
@@ -21,12 +21,35 @@ pub trait LevelSizer {
         self.max_size_segments(kv.kv_len())
     }
 
+    /// Returns number of 64-bit segments to use for the level `level_nr` (counting from 0)
+    /// and given sequence of retained `values`. By default, the level number is ignored.
+    #[inline] fn size_segments_for_level_values<VIt, F>(&self, _level_nr: usize, values: F, values_len: usize, bits_per_value: u8) -> usize
+        where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        self.size_segments_for_values(values, values_len, bits_per_value)
+    }
+
+    /// Returns number of 64-bit segments to use for the level `level_nr` (counting from 0)
+    /// and given sequence of retained `values`. By default, the level number is ignored.
+    #[inline] fn size_segments_for_level<K, KV: KVSet<K>>(&self, _level_nr: usize, kv: &KV) -> usize
+    {
+        self.size_segments(kv)
+    }
 
     /// Returns maximal number of segment that can be returned by `size_segments` for level of size `max_level_size` or less.
     fn max_size_segments(&self, max_level_size: usize) -> usize;
 }
 
+/// Returns `size * percent / 100`, rounded up, without overflowing (for any `percent`).
+#[inline] fn percent_of(size: usize, percent: u16) -> usize {
+    let percent = percent as usize;
+    (size / 100).saturating_mul(percent).saturating_add(ceiling_div(size % 100 * percent, 100))
+}
+
 /// Choose level size as a percent of the input size.
+///
+/// Percents above 100 are allowed and make levels larger than the number of keys they are built for.
+/// This reduces the number of collisions and levels (and so query time) at the cost of space.
 #[derive(Copy, Clone)]
 pub struct ProportionalLevelSize {
     pub percent: u16
@@ -42,7 +65,7 @@ impl Default for ProportionalLevelSize {
 
 impl LevelSizer for ProportionalLevelSize {
     fn max_size_segments(&self, max_level_size: usize) -> usize {
-        ceiling_div(max_level_size*self.percent as usize, 64*100)
+        percent_of(max_level_size, self.percent).div_ceil(64)
     }
 }
 
@@ -262,7 +285,7 @@ impl<LSC> ResizedLevel<LSC> {
     }
 
     #[inline(always)] fn resized(&self, size: usize) -> usize {
-        ceiling_div(size * self.percent as usize, 100)
+        percent_of(size, self.percent)
     }
 }
 
@@ -278,7 +301,196 @@ impl<LSC: LevelSizer> LevelSizer for ResizedLevel<LSC> {
         self.resized(self.level_size_chooser.size_segments(kv))
     }
 
+    #[inline] fn size_segments_for_level_values<VIt, F>(&self, level_nr: usize, values: F, values_len: usize, bits_per_value: u8) -> usize
+    where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        self.resized(self.level_size_chooser.size_segments_for_level_values(level_nr, values, values_len, bits_per_value))
+    }
+
+    fn size_segments_for_level<K, KV: KVSet<K>>(&self, level_nr: usize, kv: &KV) -> usize
+    {
+        self.resized(self.level_size_chooser.size_segments_for_level(level_nr, kv))
+    }
+
+    /// Returns the maximal size of the inner sizer (which is given in segments, like the sizes it resizes), resized.
     #[inline] fn max_size_segments(&self, max_level_size: usize) -> usize {
-        self.resized(max_level_size)
+        self.resized(self.level_size_chooser.max_size_segments(max_level_size))
+    }
+}
+
+impl<LSC: fmt::Display> fmt::Display for ResizedLevel<LSC> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_resized{}percent", self.level_size_chooser, self.percent)
     }
-}
\ No newline at end of file
+}
+
+/// Level sizer chosen at runtime among a few strategies.
+/// Allows comparing different strategies in a single collection (see [`crate::sweep_size_estimates`]).
+#[derive(Copy, Clone)]
+pub enum LevelSizeStrategy {
+    Optimal(OptimalLevelSize),
+    Proportional(ProportionalLevelSize),
+    ResizedOptimal(ResizedLevel<OptimalLevelSize>),
+    ResizedProportional(ResizedLevel<ProportionalLevelSize>)
+}
+
+impl From<OptimalLevelSize> for LevelSizeStrategy {
+    #[inline] fn from(level_sizer: OptimalLevelSize) -> Self { Self::Optimal(level_sizer) }
+}
+
+impl From<ProportionalLevelSize> for LevelSizeStrategy {
+    #[inline] fn from(level_sizer: ProportionalLevelSize) -> Self { Self::Proportional(level_sizer) }
+}
+
+impl From<ResizedLevel<OptimalLevelSize>> for LevelSizeStrategy {
+    #[inline] fn from(level_sizer: ResizedLevel<OptimalLevelSize>) -> Self { Self::ResizedOptimal(level_sizer) }
+}
+
+impl From<ResizedLevel<ProportionalLevelSize>> for LevelSizeStrategy {
+    #[inline] fn from(level_sizer: ResizedLevel<ProportionalLevelSize>) -> Self { Self::ResizedProportional(level_sizer) }
+}
+
+/// Calls `$call` on the level sizer wrapped by `$strategy`, bound to `$ls`.
+macro_rules! with_level_sizer {
+    ($strategy:expr, $ls:ident => $call:expr) => {
+        match $strategy {
+            LevelSizeStrategy::Optimal($ls) => $call,
+            LevelSizeStrategy::Proportional($ls) => $call,
+            LevelSizeStrategy::ResizedOptimal($ls) => $call,
+            LevelSizeStrategy::ResizedProportional($ls) => $call
+        }
+    };
+}
+
+impl LevelSizer for LevelSizeStrategy {
+    #[inline] fn size_segments_for_values<VIt, F>(&self, values: F, values_len: usize, bits_per_value: u8) -> usize
+    where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        with_level_sizer!(self, ls => ls.size_segments_for_values(values, values_len, bits_per_value))
+    }
+
+    fn size_segments<K, KV: KVSet<K>>(&self, kv: &KV) -> usize
+    {
+        with_level_sizer!(self, ls => ls.size_segments(kv))
+    }
+
+    #[inline] fn size_segments_for_level_values<VIt, F>(&self, level_nr: usize, values: F, values_len: usize, bits_per_value: u8) -> usize
+    where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        with_level_sizer!(self, ls => ls.size_segments_for_level_values(level_nr, values, values_len, bits_per_value))
+    }
+
+    fn size_segments_for_level<K, KV: KVSet<K>>(&self, level_nr: usize, kv: &KV) -> usize
+    {
+        with_level_sizer!(self, ls => ls.size_segments_for_level(level_nr, kv))
+    }
+
+    #[inline] fn max_size_segments(&self, max_level_size: usize) -> usize {
+        with_level_sizer!(self, ls => ls.max_size_segments(max_level_size))
+    }
+}
+
+impl fmt::Display for LevelSizeStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        with_level_sizer!(self, ls => ls.fmt(f))
+    }
+}
+
+/// Chooses the sizes of the first `max_levels-1` levels by another chooser (usually [`OptimalLevelSize`])
+/// and makes each next level sparse, to resolve almost all its keys.
+///
+/// Each level, starting from the level `max_levels-1`, built for `n` keys gets `n` 64-bit segments (64 bits per key),
+/// so only about 1/64 of its keys collide and are left for the next level.
+/// So `max_levels` is a soft bound: the number of levels can exceed it by a few (logarithmically in the number of keys).
+/// A hard bound would require the sparse level to exclude collisions, and so to be quadratic in the number of its keys.
+/// The space used by the sparse levels is linear in the number of keys left for them.
+/// If `max_levels` is `0` or `1`, all levels are sparse.
+/// Note that the number of levels cannot be smaller than the length of the longest codeword of a compressed map.
+#[derive(Copy, Clone)]
+pub struct SoftMaxLevels<LSC>(pub LSC, pub usize);
+
+impl<LSC> SoftMaxLevels<LSC> {
+    /// Returns the number of 64-bit segments of the sparse level built for `values_len` keys.
+    #[inline] fn last_level_segments(values_len: usize) -> usize {
+        values_len.max(1)
+    }
+}
+
+impl<LSC: LevelSizer> LevelSizer for SoftMaxLevels<LSC> {
+    #[inline] fn size_segments_for_values<VIt, F>(&self, values: F, values_len: usize, bits_per_value: u8) -> usize
+    where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        self.0.size_segments_for_values(values, values_len, bits_per_value)
+    }
+
+    fn size_segments<K, KV: KVSet<K>>(&self, kv: &KV) -> usize
+    {
+        self.0.size_segments(kv)
+    }
+
+    fn size_segments_for_level_values<VIt, F>(&self, level_nr: usize, values: F, values_len: usize, bits_per_value: u8) -> usize
+    where VIt: IntoIterator<Item = u64>, F: FnMut() -> VIt
+    {
+        if level_nr + 1 >= self.1 {
+            Self::last_level_segments(values_len)
+        } else {
+            self.0.size_segments_for_level_values(level_nr, values, values_len, bits_per_value)
+        }
+    }
+
+    fn size_segments_for_level<K, KV: KVSet<K>>(&self, level_nr: usize, kv: &KV) -> usize
+    {
+        if level_nr + 1 >= self.1 {
+            Self::last_level_segments(kv.kv_len())
+        } else {
+            self.0.size_segments_for_level(level_nr, kv)
+        }
+    }
+
+    #[inline] fn max_size_segments(&self, max_level_size: usize) -> usize {
+        Self::last_level_segments(max_level_size).max(self.0.max_size_segments(max_level_size))
+    }
+}
+
+impl<LSC: fmt::Display> fmt::Display for SoftMaxLevels<LSC> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_softmax{}levels", self.0, self.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_percent_of() {
+        assert_eq!(percent_of(1000, 150), 1500);
+        assert_eq!(percent_of(1001, 150), 1502);
+        assert_eq!(percent_of(7, 100), 7);
+        assert_eq!(percent_of(0, u16::MAX), 0);
+        assert_eq!(percent_of(usize::MAX, 50), usize::MAX / 2 + 1);
+        assert_eq!(percent_of(usize::MAX, u16::MAX), usize::MAX);
+        assert_eq!(ProportionalLevelSize::with_percent(150).max_size_segments(64 * 100), 150);
+        assert_eq!(ProportionalLevelSize::with_percent(u16::MAX).max_size_segments(usize::MAX), usize::MAX / 64 + 1);
+        assert_eq!(ResizedLevel::new(50, ProportionalLevelSize::with_percent(200)).max_size_segments(64 * 100), 100);
+    }
+
+    fn check_resized_max_size_segments<LSC: LevelSizer>(inner: LSC) {
+        let resized = ResizedLevel::new(150, inner);
+        for values_len in [1, 63, 64, 1000, 12345] {
+            let values = || (0..values_len as u64).map(|v| v % 4);
+            let max = resized.max_size_segments(values_len);
+            // the bound is given in segments, like the bound of the inner sizer:
+            assert_eq!(max, percent_of(resized.level_size_chooser.max_size_segments(values_len), 150));
+            assert!(resized.size_segments_for_values(values, values_len, 2) <= max);
+        }
+    }
+
+    #[test]
+    fn test_resized_max_size_segments() {
+        check_resized_max_size_segments(OptimalLevelSize);
+        check_resized_max_size_segments(ProportionalLevelSize::with_percent(80));
+        check_resized_max_size_segments(ProportionalLevelSize::with_percent(300));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::fp::collision_solver::{CollisionSolverBuilder, LoMemAcceptEquals};
-use crate::fp::OptimalLevelSize;
+use crate::fp::{OptimalLevelSize, WithSeed};
 use ph::{BuildDefaultSeededHasher, BuildSeededHasher};
 
 /// Configuration accepted by [`fp::Map`](crate::fp::Map) constructors.
@@ -77,3 +77,12 @@ impl<LSC, CS: CollisionSolverBuilder, S: BuildSeededHasher> MapConf<LSC, CS, S>
     }
 }
 
+impl<LSC, CS: CollisionSolverBuilder, S: BuildSeededHasher> MapConf<LSC, CS, S> {
+    /// Returns the configuration that mixes `seed` into the hash functions of `self` (see [`WithSeed`]).
+    ///
+    /// Changing the seed changes the positions of keys at each level, and so which keys collide and the level sizes.
+    /// Reading the map requires the same [`WithSeed`] hasher.
+    pub fn with_seed(self, seed: u64) -> MapConf<LSC, CS, WithSeed<S>> {
+        MapConf { level_sizer: self.level_sizer, collision_solver: self.collision_solver, hash: WithSeed::new(self.hash, seed) }
+    }
+}
@@ -19,6 +19,10 @@ use dyn_size_of::GetSize;
 /// It usually takes somewhat more than *nb* bits to represent a function from an *n*-element set into a set of *b*-bit values.
 /// (Smaller sizes are achieved when the set of values is small and the same values are assigned to multiple keys.)
 /// The expected time complexities of its construction and evaluation are *O(n)* and *O(1)*, respectively.
+///
+/// All internal arrays are boxed slices of exactly the required sizes
+/// (e.g. the values occupy `ceiling_div(number of stored values * bits per value, 64)` segments),
+/// so the map does not hold any over-allocated storage that could be trimmed after construction.
 pub struct Map<S = BuildDefaultSeededHasher> {
     array: ArrayWithRank,
     values: Box<[u64]>,    // BitVec
@@ -42,12 +46,39 @@ fn index<H: BuildSeededHasher, K: Hash + ?Sized>(hash: &H, k: &K, level_nr: u64,
     ph::utils::map64_to_64(hash.hash_one(k, level_nr), level_size as u64) as usize
 }
 
+/// Hashes of the keys that reached a level, retained for [`Map::verify`].
+struct RetainedLevel {
+    /// Hashes and values of the keys resolved at the level.
+    resolved: Box<[(u64, u8)]>,
+    /// Hashes of the keys passed to the next levels.
+    unresolved: Box<[u64]>
+}
+
+impl GetSize for RetainedLevel {
+    fn size_bytes_dyn(&self) -> usize {
+        self.resolved.size_bytes_dyn() + self.unresolved.size_bytes_dyn()
+    }
+
+    const USES_DYN_MEM: bool = true;
+}
+
+/// Data needed by [`Map::verify`], retained during construction by [`Map::try_with_conf_stats_retaining`]
+/// and kept apart from the [`Map`], which therefore has no overhead when the data are not needed.
+/// Only the hashes of the keys are retained, not the keys themselves.
+pub struct RetainedKeys(Box<[RetainedLevel]>);
+
+impl GetSize for RetainedKeys {
+    fn size_bytes_dyn(&self) -> usize { self.0.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
 #[derive(Default)]
 struct Arrays {
     level_sizes: Vec::<usize>,
     arrays: Vec::<Box<[u64]>>,
     values_lens: Vec::<usize>,
-    values: Vec::<Box<[u64]>>
+    values: Vec::<Box<[u64]>>,
+    retained_keys: Option<Vec<RetainedLevel>>
 }
 
 impl Arrays {
@@ -67,6 +98,7 @@ impl Arrays {
         self.level_sizes.truncate(len);
         self.values.truncate(len);
         self.values_lens.truncate(len);
+        if let Some(r) = self.retained_keys.as_mut() { r.truncate(len); }
     }
 }
 
@@ -99,6 +131,64 @@ impl<S: BuildSeededHasher> Map<S> {
         self.get_stats(key, &mut ())
     }
 
+    /// Returns whether some level of `self` claims the given `key`, without reading its value.
+    ///
+    /// It is `true` for all keys in the input key-value collection given during construction.
+    /// For other keys, it can return either `false` or `true` (false positive),
+    /// in the cases where [`Self::get`] returns either [`None`] or a value assigned to other key.
+    pub fn may_contain<K: Hash + ?Sized>(&self, key: &K) -> bool {
+        let mut array_begin_index = 0usize;
+        for (level, level_size) in self.level_sizes.iter().enumerate() {
+            let level_size = *level_size << 6usize;
+            if self.array.content.get_bit(array_begin_index + index(&self.hash, key, level as u64, level_size)) {
+                return true;
+            }
+            array_begin_index += level_size;
+        }
+        false
+    }
+
+    /// Checks whether `self` returns the proper values for all keys given during construction, i.e.
+    /// whether each key is resolved at the same level as during construction, with the same value.
+    ///
+    /// `retained` must be obtained together with `self` from [`Self::try_with_conf_stats_retaining`].
+    pub fn verify(&self, retained: &RetainedKeys) -> bool {
+        let mut array_begin_index = 0usize;
+        for (level_size, level) in self.level_sizes.iter().zip(retained.0.iter()) {
+            let level_size = *level_size << 6usize;
+            let index = |hash: u64| array_begin_index + ph::utils::map64_to_64(hash, level_size as u64) as usize;
+            for (hash, value) in level.resolved.iter() {
+                let i = index(*hash);
+                if !self.array.content.get_bit(i) ||
+                    self.values.get_fragment(self.array.rank(i), self.bits_per_value) != *value as u64 { return false; }
+            }
+            if level.unresolved.iter().any(|hash| self.array.content.get_bit(index(*hash))) { return false; }
+            array_begin_index += level_size;
+        }
+        true
+    }
+
+    /// Returns the number of levels.
+    #[inline] pub fn levels(&self) -> usize { self.level_sizes.len() }
+
+    /// Returns the size of the given `level` in bits. Panics if `level` is not less than [`Self::levels`].
+    #[inline] pub fn level_size_bits(&self, level: usize) -> usize { self.level_sizes[level] << 6 }
+
+    /// Returns the load factor of each level, i.e. the fraction of its bits that are occupied by values of keys.
+    ///
+    /// Since keys with equal values that collide share a single bit, the number of keys placed
+    /// at a level can be larger than the number of its occupied bits.
+    /// Low load factors indicate that the levels are too large, and high ones that they are too small (causing many collisions).
+    pub fn level_load_factors(&self) -> Vec<f64> {
+        let mut begin = 0;
+        self.level_sizes.iter().map(|size| {
+            let level_bits = *size << 6;
+            let occupied = self.array.content.count_ones_in_range(begin, begin + level_bits);
+            begin += level_bits;
+            occupied as f64 / level_bits as f64
+        }).collect()
+    }
+
     /// Gets the value associated with the given `key` and reports statistics to `access_stats`.
     /// 
     /// If the `key` was not in the input key-value collection given during construction,
@@ -129,32 +219,57 @@ impl<S: BuildSeededHasher> Map<S> {
         conf: &MapConf<LSC, CSB, S>,
         stats: &mut BS,
         bits_per_value: u8,
-        construct_partial: bool
+        construct_partial: bool,
+        retain_keys: bool
     ) -> Arrays
         where K: Hash, KV: KVSet<K>, LSC: LevelSizer, CSB: CollisionSolverBuilder, BS: stats::BuildStatsCollector
     {
-        let mut res = Arrays::default();
+        let mut res = Arrays { retained_keys: retain_keys.then(Vec::new), ..Default::default() };
         let mut input_size = kv.kv_len();
         let mut level_nr = 0u64;
         let mut levels_without_reduction = 0;   // number of levels without any reduction in number of the keys
         while input_size != 0 {
-            let level_size_segments = conf.level_sizer.size_segments(kv);
+            let level_size_segments = conf.level_sizer.size_segments_for_level(level_nr as usize, kv);
             let level_size = level_size_segments * 64;
             stats.level(input_size, level_size);
             let mut collision_solver: <CSB as CollisionSolverBuilder>::CollisionSolver = conf.collision_solver.new(level_size_segments, bits_per_value);
             kv.process_all_values(|k| index(&conf.hash, k, level_nr, level_size), &mut collision_solver);
             let (current_array, current_values, current_values_len) = collision_solver.to_collision_and_values(bits_per_value);
+            let retained_level = retain_keys.then(|| {
+                let (mut resolved, mut unresolved) = (Vec::new(), Vec::new());
+                kv.for_each_key_value(|k, v| {
+                    let hash = conf.hash.hash_one(k, level_nr);
+                    if current_array.get_bit(ph::utils::map64_to_64(hash, level_size as u64) as usize) {
+                        resolved.push((hash, v))
+                    } else {
+                        unresolved.push(hash)
+                    }
+                });
+                RetainedLevel { resolved: resolved.into_boxed_slice(), unresolved: unresolved.into_boxed_slice() }
+            });
             kv.retain_keys(|k| !current_array.get_bit(index(&conf.hash, k, level_nr, level_size)));
 
             let prev_input_size = input_size;
             input_size = kv.kv_len();
+            // levels without reduction are reported only when it is known that they are kept
+            let report_unreduced = |stats: &mut BS, level_sizes: &[usize], levels_without_reduction: usize| {
+                for (nr, size) in level_sizes.iter().enumerate().skip(level_sizes.len() - levels_without_reduction) {
+                    stats.level_resolved(nr, prev_input_size, 0, size * 64);
+                }
+            };
             if input_size == prev_input_size {
                 if levels_without_reduction == 9 /*+1*/ {
-                    if construct_partial { res.truncate(res.arrays.len()-levels_without_reduction); }
+                    if construct_partial {
+                        res.truncate(res.arrays.len()-levels_without_reduction);
+                    } else {
+                        report_unreduced(stats, &res.level_sizes, levels_without_reduction);
+                    }
                     break;
                 }
                 levels_without_reduction += 1;
             } else {
+                report_unreduced(stats, &res.level_sizes, levels_without_reduction);
+                stats.level_resolved(level_nr as usize, prev_input_size, prev_input_size - input_size, level_size);
                 levels_without_reduction = 0;
             }
 
@@ -162,6 +277,7 @@ impl<S: BuildSeededHasher> Map<S> {
             res.level_sizes.push(level_size_segments);
             res.values.push(current_values);
             res.values_lens.push(current_values_len);
+            if let (Some(r), Some(l)) = (res.retained_keys.as_mut(), retained_level) { r.push(l); }
             level_nr += 1;
         }
         stats.end(input_size);
@@ -191,12 +307,25 @@ impl<S: BuildSeededHasher> Map<S> {
         where K: Hash, KV: KVSet<K>, LSC: LevelSizer, CSB: CollisionSolverBuilder, BS: stats::BuildStatsCollector
     {
         let bits_per_value = kv.bits_per_value();
-        let res = Self::_with_conf_stats(&mut kv, &conf, stats, bits_per_value, false);
+        let res = Self::_with_conf_stats(&mut kv, &conf, stats, bits_per_value, false, false);
         if kv.kv_len() != 0 { return None; }
         drop(kv);
         Some(res.into_map(conf.hash, bits_per_value))
     }
 
+    /// Constructs [`Map`] like [`Self::try_with_conf_stats`], and additionally returns
+    /// the hashes of the keys (and their values) needed by [`Self::verify`].
+    pub fn try_with_conf_stats_retaining<K, KV, LSC, CSB, BS>(mut kv: KV, conf: MapConf<LSC, CSB, S>, stats: &mut BS) -> Option<(Self, RetainedKeys)>
+        where K: Hash, KV: KVSet<K>, LSC: LevelSizer, CSB: CollisionSolverBuilder, BS: stats::BuildStatsCollector
+    {
+        let bits_per_value = kv.bits_per_value();
+        let mut res = Self::_with_conf_stats(&mut kv, &conf, stats, bits_per_value, false, true);
+        if kv.kv_len() != 0 { return None; }
+        drop(kv);
+        let retained = RetainedKeys(res.retained_keys.take().unwrap_or_default().into_boxed_slice());
+        Some((res.into_map(conf.hash, bits_per_value), retained))
+    }
+
     /// Constructs [`Map`] for given key-value pairs `kv`,
     /// using the build configuration `conf` and reporting statistics with `stats`.
     /// 
@@ -212,7 +341,7 @@ impl<S: BuildSeededHasher> Map<S> {
         where K: Hash, KV: KVSet<K>, LSC: LevelSizer, CSB: CollisionSolverBuilder, BS: stats::BuildStatsCollector
     {
         let bits_per_value = kv.bits_per_value();
-        let res = Self::_with_conf_stats(&mut kv, &conf, stats, bits_per_value, true);
+        let res = Self::_with_conf_stats(&mut kv, &conf, stats, bits_per_value, true, false);
         if kv.kv_len() != 0 { return Err((res.into_map(conf.hash, kv.bits_per_value()), kv)); }
         drop(kv);
         Ok(res.into_map(conf.hash, bits_per_value))
@@ -351,6 +480,120 @@ mod tests {
         test_read_write(&fpmap);
     }
 
+    /// Records the arguments of [`stats::BuildStatsCollector::level_resolved`] calls.
+    #[derive(Default)]
+    struct LevelsRecorder(Vec<(usize, usize, usize, usize)>);
+
+    impl stats::BuildStatsCollector for LevelsRecorder {
+        fn level_resolved(&mut self, level_nr: usize, input_size: usize, resolved_keys: usize, level_size: usize) {
+            self.0.push((level_nr, input_size, resolved_keys, level_size));
+        }
+    }
+
+    #[test]
+    fn level_statistics() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k % 4) as u8)).collect();
+        let mut recorder = LevelsRecorder::default();
+        let fpmap = Map::with_map_conf(&kv, MapConf::default(), &mut recorder);
+        assert_eq!(recorder.0.len(), fpmap.level_sizes.len());
+        let mut remaining = kv.len();
+        for (i, (level_nr, input_size, resolved_keys, level_size)) in recorder.0.iter().copied().enumerate() {
+            assert_eq!(level_nr, i);
+            assert_eq!(input_size, remaining);
+            assert_eq!(level_size, fpmap.level_sizes[i] * 64);
+            remaining -= resolved_keys;
+        }
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn level_statistics_partial() {
+        let mut keys: Vec<u32> = (0..1000).chain([7]).collect();   // key 7 is duplicated with different value
+        let mut values: Vec<u8> = (0..1000).map(|k| (k % 4) as u8).chain([2]).collect();
+        let mut recorder = LevelsRecorder::default();
+        let (fpmap, rest) = Map::try_with_conf_stats_or_partial(SlicesMutSource::new(&mut keys, &mut values, 0), MapConf::default(), &mut recorder)
+            .err().unwrap();
+        assert_eq!(rest.kv_len(), 2);
+        assert_eq!(recorder.0.len(), fpmap.level_sizes.len());
+        for (i, (level_nr, _, resolved_keys, level_size)) in recorder.0.iter().copied().enumerate() {
+            assert_eq!(level_nr, i);
+            assert_eq!(level_size, fpmap.level_sizes[i] * 64);
+            assert!(resolved_keys > 0);
+        }
+    }
+
+    #[test]
+    fn may_contain() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k % 4) as u8)).collect();
+        let fpmap = Map::with_map_conf(&kv, MapConf::default(), &mut ());
+        for k in kv.keys() { assert!(fpmap.may_contain(k)); }
+        assert!((1000..11000u32).any(|k| !fpmap.may_contain(&k)));
+    }
+
+    #[test]
+    fn verify() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k % 5) as u8)).collect();
+        let (mut keys, mut values): (Vec<u32>, Vec<u8>) = kv.iter().map(|(k, v)| (*k, *v)).unzip();
+        let (fpmap, retained) = Map::try_with_conf_stats_retaining(
+            SlicesMutSource::new(&mut keys, &mut values, 0), MapConf::default(), &mut ()).unwrap();
+        assert!(fpmap.verify(&retained));
+        let without_keys = Map::with_map_conf(&kv, MapConf::default(), &mut ());
+        assert_eq!(fpmap.size_bytes(), without_keys.size_bytes());
+        assert!(retained.size_bytes() > 0);
+        let mut broken = fpmap;
+        broken.values.iter_mut().for_each(|v| *v = !*v);
+        assert!(!broken.verify(&retained));
+    }
+
+    #[test]
+    fn levels() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k % 4) as u8)).collect();
+        let fpmap = Map::with_map_conf(&kv, MapConf::default(), &mut ());
+        assert!(fpmap.levels() >= 1);
+        let sizes: Vec<usize> = (0..fpmap.levels()).map(|level| fpmap.level_size_bits(level)).collect();
+        assert!(sizes.windows(2).all(|w| w[0] >= w[1]), "{sizes:?}");
+        assert_eq!(sizes.iter().sum::<usize>(), fpmap.array.content.len() * 64);
+    }
+
+    #[test]
+    fn level_load_factors() {
+        let kv: HashMap<u32, u8> = (0..5000).map(|k| (k, (k % 7) as u8)).collect();
+        let fpmap = Map::with_map_conf(&kv, MapConf::default(), &mut ());
+        let load_factors = fpmap.level_load_factors();
+        assert_eq!(load_factors.len(), fpmap.levels());
+        assert!(load_factors.iter().all(|f| *f > 0.0 && *f <= 1.0), "{load_factors:?}");
+        let occupied: f64 = load_factors.iter().enumerate().map(|(level, f)| f * fpmap.level_size_bits(level) as f64).sum();
+        assert_eq!(occupied.round() as usize, fpmap.array.content.count_bit_ones());
+    }
+
+    #[test]
+    fn soft_max_levels() {
+        let kv: HashMap<u32, u8> = (0..100_000).map(|k| (k, (k % 7) as u8)).collect();
+        for max_levels in [1, 3] {
+            let conf = MapConf::lsize(crate::fp::SoftMaxLevels(crate::fp::OptimalLevelSize, max_levels));
+            let fpmap = Map::with_map_conf(&kv, conf, &mut ());
+            let levels_bits: usize = (0..fpmap.levels()).map(|level| fpmap.level_size_bits(level)).sum();
+            // each sparse level leaves about 1/64 of its keys, so 3 of them are enough for 100_000 keys:
+            assert!(fpmap.levels() <= max_levels + 3, "{:?}", fpmap.level_sizes);
+            assert!(levels_bits <= 70 * kv.len(), "{:?}", fpmap.level_sizes);    // linear in the number of keys
+            for (k, v) in kv.iter() { assert_eq!(fpmap.get(k), Some(*v as u64)); }
+        }
+    }
+
+    #[test]
+    fn with_seed() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k % 4) as u8)).collect();
+        let serialized = |seed| {
+            let mut buff = Vec::new();
+            Map::with_map_conf(&kv, MapConf::default().with_seed(seed), &mut ()).write(&mut buff).unwrap();
+            buff
+        };
+        assert_eq!(serialized(3), serialized(3));
+        assert_ne!(serialized(3), serialized(4));
+        let fpmap = Map::read_with_hasher(&mut &serialized(3)[..], crate::fp::WithSeed::new(BuildDefaultSeededHasher::default(), 3)).unwrap();
+        for (k, v) in kv.iter() { assert_eq!(fpmap.get(k), Some(*v as u64)); }
+    }
+
     #[test]
     fn with_hashmap_4pairs() {
         test_4pairs(MapConf::default());
@@ -377,6 +620,26 @@ mod tests {
         test_8pairs(MapConf::default());
     }
 
+    #[test]
+    fn min_collision() {
+        let kv: HashMap<u32, u8> = (0..1000).map(|k| (k, (k * 7 % 8) as u8)).collect();
+        let fpmap = Map::with_map_conf(&kv, MapConf::cs(crate::fp::collision_solver::MinCollision), &mut ());
+        assert_eq!(fpmap.level_sizes.len(), 1);
+        let level_size = fpmap.level_sizes[0] << 6;
+        let mut minimum = HashMap::<usize, u8>::new();
+        for (k, v) in kv.iter() {
+            let m = minimum.entry(index(&fpmap.hash, k, 0, level_size)).or_insert(*v);
+            *m = (*m).min(*v);
+        }
+        assert!(minimum.len() < kv.len());  // some keys collide
+        for (k, v) in kv.iter() {
+            let expected = minimum[&index(&fpmap.hash, k, 0, level_size)];
+            assert!(expected <= *v);
+            assert_eq!(fpmap.get(k), Some(expected as u64));
+        }
+        test_fpmap_invariants(&fpmap);
+    }
+
     #[test]
     fn test_fail_partial() {
         let mut k = ['a', 'b', 'a', 'c'];
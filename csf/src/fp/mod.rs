@@ -3,24 +3,36 @@
 pub mod kvset;
 
 mod map;
-pub use map::{Map, MapConf};
+pub use map::{Map, MapConf, RetainedKeys};
 
 mod cmap;
-pub use cmap::{CMap, CMapConf};
+pub use cmap::{CMap, CMapConf, DuplicateKeyError, BorrowedCMap};
+
+mod bytesmap;
+pub use bytesmap::BytesMap;
+
+mod hybrid;
+pub use hybrid::HybridMap;
 
 //mod gomap;
 //pub use gomap::{GOMap, GOMapConf};
 
 mod gocmap;
-pub use gocmap::{GOCMap, GOCMapConf};
+pub use gocmap::{GOCMap, GOCMapConf, ConfError, SizeBreakdown};
 pub use ph::fmph::{GroupSize, SeedSize, TwoToPowerBits, TwoToPowerBitsStatic, Bits, Bits8, GOConf};
 
 pub mod level_sizer;
-pub use level_sizer::{LevelSizer, ProportionalLevelSize, OptimalLevelSize, ResizedLevel};
+pub use level_sizer::{LevelSizer, ProportionalLevelSize, OptimalLevelSize, ResizedLevel, SoftMaxLevels, LevelSizeStrategy};
 
 pub mod collision_solver;
 pub use collision_solver::{CollisionSolver, CollisionSolverBuilder, IsLossless, LoMemAcceptEquals};
 
+pub mod size_estimate;
+
+mod seed;
+pub use seed::WithSeed;
+
 
 
-mod common;
\ No newline at end of file
+mod common;
+pub use common::{encode_all, encode_into_bits};
\ No newline at end of file
@@ -0,0 +1,25 @@
+use ph::BuildSeededHasher;
+
+/// [`BuildSeededHasher`] that mixes the given `seed` into the seeds of the hash functions of `hasher`.
+///
+/// With `seed` equal to `0`, it calculates exactly the same hashes as `hasher`.
+#[derive(Default, Copy, Clone)]
+pub struct WithSeed<S> {
+    /// The underlying family of hash functions.
+    pub hasher: S,
+    /// The seed mixed into the seeds of hash functions.
+    pub seed: u64
+}
+
+impl<S> WithSeed<S> {
+    /// Returns [`WithSeed`] that mixes `seed` into the seeds of hash functions of `hasher`.
+    #[inline] pub fn new(hasher: S, seed: u64) -> Self { Self { hasher, seed } }
+}
+
+impl<S: BuildSeededHasher> BuildSeededHasher for WithSeed<S> {
+    type Hasher = S::Hasher;
+
+    #[inline(always)] fn build_hasher(&self, seed: u64) -> Self::Hasher {
+        self.hasher.build_hasher(seed ^ self.seed.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+}
@@ -0,0 +1,85 @@
+//! Estimating the size of [`CMap`](super::CMap) without building it.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use dyn_size_of::GetSize;
+use minimum_redundancy::BitsPerFragment;
+use super::LevelSizer;
+
+/// Maximum number of levels simulated by [`cmap_size_bytes`].
+const MAX_LEVELS: usize = 1000;
+
+/// Returns the expected number of bytes occupied by [`CMap`](super::CMap) that maps `num_keys` keys
+/// to values distributed according to `frequencies`, when the values are Huffman-coded with
+/// `bits_per_fragment` bits per fragment and the level sizes are chosen by `level_sizer`.
+/// Returns [`None`] if `bits_per_fragment` is not in range [1, 8] (supported by [`CMap`](super::CMap)).
+///
+/// The estimation simulates the construction (with lossless collision solver) in expectation,
+/// without hashing any keys. However, at each simulated level, `level_sizer` is given
+/// the (rounded) expected fragments of all keys entering the level, so the cost of each level is
+/// proportional to the number of keys reaching it, which is up to `num_keys`.
+pub fn cmap_size_bytes<V, LSC>(num_keys: usize, frequencies: &HashMap<V, u32>, bits_per_fragment: u8, level_sizer: &LSC) -> Option<usize>
+    where V: Hash + Eq + Ord + Clone + GetSize, LSC: LevelSizer
+{
+    let coding = minimum_redundancy::Coding::from_frequencies_cloned(BitsPerFragment::new(bits_per_fragment).filter(|b| b.get() <= 8)?, frequencies);
+    let total_frequency: f64 = frequencies.values().map(|f| *f as f64).sum();
+    // for each value: its fragments and expected number of unresolved keys at each fragment position
+    let mut values: Vec<(Vec<u32>, Vec<f64>)> = coding.codes().filter_map(|(v, c)| {
+        let freq = *frequencies.get(v)? as f64;
+        if c.len == 0 || freq == 0.0 { return None; }
+        let mut counts = vec![0.0; c.len as usize];
+        counts[0] = freq * num_keys as f64 / total_frequency;
+        Some((c.iter(coding.degree).collect(), counts))
+    }).collect();
+    let fragments_count = 1usize << bits_per_fragment;
+    let mut total_bits = 0.0;
+    let mut levels = 0;
+    while levels < MAX_LEVELS {
+        let mut histogram = vec![0.0f64; fragments_count];
+        for (fragments, counts) in &values {
+            for (f, c) in fragments.iter().zip(counts) { histogram[*f as usize] += c; }
+        }
+        let input_size: f64 = histogram.iter().sum();
+        if input_size < 0.5 { break; }
+        let rounded: Vec<usize> = histogram.iter().map(|c| c.round() as usize).collect();
+        let rounded_size = rounded.iter().sum::<usize>().max(1);
+        let level_size_segments = level_sizer.size_segments_for_level_values(levels,
+            || rounded.iter().enumerate().flat_map(|(f, c)| std::iter::repeat_n(f as u64, *c)),
+            rounded_size, bits_per_fragment).max(1);
+        let level_size = (level_size_segments * 64) as f64;
+        let lambda = input_size / level_size;
+        // probability that the key with given fragment is not collided with key with different fragment:
+        let resolved: Vec<f64> = histogram.iter().map(|c| (-lambda * (1.0 - c / input_size)).exp()).collect();
+        let stored: f64 = histogram.iter().zip(&resolved)
+            .map(|(c, r)| r * (1.0 - (-lambda * c / input_size).exp())).sum::<f64>() * level_size;
+        total_bits += level_size + level_size / 32.0 + stored * bits_per_fragment as f64;
+        for (fragments, counts) in values.iter_mut() {
+            for pos in (0..counts.len()).rev() {
+                let moved = counts[pos] * resolved[fragments[pos] as usize];
+                counts[pos] -= moved;
+                if pos + 1 < counts.len() { counts[pos + 1] += moved; }
+            }
+        }
+        levels += 1;
+    }
+    Some((total_bits / 8.0).ceil() as usize + levels * std::mem::size_of::<u64>() + coding.size_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fp::ProportionalLevelSize;
+    use maplit::hashmap;
+
+    #[test]
+    fn test_cmap_size_bytes() {
+        let freq = hashmap!('a' => 60u32, 'b' => 30, 'c' => 10);
+        let small = cmap_size_bytes(1000, &freq, 1, &ProportionalLevelSize::default()).unwrap();
+        let large = cmap_size_bytes(100000, &freq, 1, &ProportionalLevelSize::default()).unwrap();
+        assert!(small > 0);
+        assert!(large > 50 * small);
+        for invalid_bits_per_fragment in [0, 9, 32, u8::MAX] {
+            assert!(cmap_size_bytes(1000, &freq, invalid_bits_per_fragment, &ProportionalLevelSize::default()).is_none());
+        }
+    }
+}
@@ -45,6 +45,8 @@ pub mod ls;
 
 pub use dyn_size_of::GetSize;
 pub use bitm::bits_to_store;
+use std::collections::HashMap;
+use std::hash::Hash;
 
 /// Calculates the minimal number of bits needed to store any of the given `values`.
 /// 
@@ -66,6 +68,141 @@ pub fn bits_to_store_any_of<V: Into<u64>>(values: impl IntoIterator<Item = V>) -
     bits_to_store_any_of(values.into_iter().cloned())
 }
 
+/// Estimates (using [`fp::size_estimate::cmap_size_bytes`]) the size in bytes of [`fp::CMap`]
+/// that maps `num_keys` keys to values distributed according to `frequencies`,
+/// for each combination of bits per fragment (taken from `bits_per_fragment_range`) and level sizer (taken from `strategies`).
+/// The strategies can be of different kinds, see [`fp::LevelSizeStrategy`].
+///
+/// Returns the vector of pairs: (bits per fragment, level sizer) and the estimated size in bytes.
+/// The numbers of bits per fragment out of range [1, 8] are skipped.
+pub fn sweep_size_estimates<V>(num_keys: usize, frequencies: &HashMap<V, u32>, bits_per_fragment_range: impl IntoIterator<Item = u8>, strategies: &[fp::LevelSizeStrategy])
+    -> Vec<((u8, fp::LevelSizeStrategy), usize)>
+    where V: Hash + Eq + Ord + Clone + GetSize
+{
+    bits_per_fragment_range.into_iter().flat_map(|bits_per_fragment|
+        strategies.iter().filter_map(move |level_sizer| Some((
+            (bits_per_fragment, *level_sizer),
+            fp::size_estimate::cmap_size_bytes(num_keys, frequencies, bits_per_fragment, level_sizer)?
+        )))
+    ).collect()
+}
+
+/// Compressed static function built by [`build_within_latency`]: either [`fp::CMap`] or [`fp::GOCMap`].
+pub enum CompressedMap<V> {
+    CMap(fp::CMap<minimum_redundancy::Coding<V>>),
+    GOCMap(fp::GOCMap<minimum_redundancy::Coding<V>, fp::TwoToPowerBits, fp::Bits>)
+}
+
+impl<V: Hash + Eq + Clone> CompressedMap<V> {
+    /// Gets the value associated with the given key `k` and reports statistics to `access_stats`.
+    pub fn get_stats<K: Hash + ?Sized, A: ph::stats::AccessStatsCollector>(&self, k: &K, access_stats: &mut A) -> Option<&V> {
+        match self {
+            Self::CMap(map) => map.get_stats(k, access_stats),
+            Self::GOCMap(map) => map.get_stats(k, access_stats)
+        }
+    }
+
+    /// Gets the value associated with the given key `k`.
+    #[inline] pub fn get<K: Hash + ?Sized>(&self, k: &K) -> Option<&V> {
+        self.get_stats(k, &mut ())
+    }
+
+    /// Returns the coding used to compress values.
+    pub fn value_coding(&self) -> &minimum_redundancy::Coding<V> {
+        match self {
+            Self::CMap(map) => map.value_coding(),
+            Self::GOCMap(map) => map.value_coding()
+        }
+    }
+
+    /// Returns the number of levels.
+    pub fn levels(&self) -> usize {
+        match self {
+            Self::CMap(map) => map.levels(),
+            Self::GOCMap(map) => map.levels()
+        }
+    }
+}
+
+impl<V: GetSize> GetSize for CompressedMap<V> {
+    fn size_bytes_dyn(&self) -> usize {
+        match self {
+            Self::CMap(map) => map.size_bytes_dyn(),
+            Self::GOCMap(map) => map.size_bytes_dyn()
+        }
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+/// Pairs (log2 of bits per group, bits per seed) of [`fp::GOCMap`] candidates checked by [`build_within_latency`].
+const LATENCY_SEARCH_GROUPS_SEEDS: [(u8, u8); 4] = [(4, 1), (4, 2), (5, 2), (5, 4)];
+
+/// Replaces `best` (a pair of the size in bytes and the map) by `map` if `map` is smaller
+/// and the average number of levels probed per each of the `keys` is at most `max_expected_probes`.
+fn keep_if_smaller_within_latency<K: Hash, V: Hash + Eq + Clone + GetSize>(best: &mut Option<(usize, CompressedMap<V>)>,
+    map: CompressedMap<V>, keys: &[K], max_expected_probes: f64)
+{
+    let size = map.size_bytes();
+    if best.as_ref().is_some_and(|(best_size, _)| *best_size <= size) { return; }
+    let mut probes = 0usize;
+    for k in keys { map.get_stats(k, &mut probes); }
+    if probes as f64 <= max_expected_probes * keys.len() as f64 { *best = Some((size, map)); }
+}
+
+/// Returns the smallest [`CompressedMap`] that maps `keys` to `values` (distributed according to `frequencies`) and satisfies the latency constraints:
+/// the average number of levels probed per key is at most `max_expected_probes`, and
+/// the longest codeword has at most `max_code_len` bits.
+///
+/// The search covers bits per fragment in `1..=8` and several level sizers
+/// ([`fp::OptimalLevelSize`] and [`fp::ProportionalLevelSize`] with various percents).
+/// For each such combination, [`fp::CMap`] and [`fp::GOCMap`] with a few group and seed sizes are checked.
+/// The combinations are checked in ascending order of the sizes estimated by [`fp::size_estimate::cmap_size_bytes`],
+/// and the ones whose codewords are too long or whose estimated size is not smaller than the size of the best map found so far are skipped
+/// (note that the estimate concerns [`fp::CMap`], so this is a heuristic for [`fp::GOCMap`]).
+/// Returns [`None`] if none of the candidates satisfies the constraints,
+/// or if `keys` and `values` are of different lengths, or some of the `values` has no positive frequency in `frequencies`.
+pub fn build_within_latency<K, V>(keys: &[K], values: &[V], frequencies: &HashMap<V, u32>, max_expected_probes: f64, max_code_len: u8)
+    -> Option<CompressedMap<V>>
+    where K: Hash + Clone, V: Hash + Eq + Ord + Clone + GetSize
+{
+    if keys.len() != values.len() || !values.iter().all(|v| frequencies.get(v).is_some_and(|f| *f > 0)) { return None; }
+    let strategies: [fp::LevelSizeStrategy; 6] = [
+        fp::OptimalLevelSize.into(),
+        fp::ProportionalLevelSize::with_percent(50).into(),
+        fp::ProportionalLevelSize::with_percent(80).into(),
+        fp::ProportionalLevelSize::with_percent(100).into(),
+        fp::ProportionalLevelSize::with_percent(150).into(),
+        fp::ProportionalLevelSize::with_percent(200).into(),
+    ];
+    let mut combinations: Vec<_> = (1..=8).filter_map(minimum_redundancy::BitsPerFragment::new)
+        .filter(|bits_per_fragment| {
+            let coding = minimum_redundancy::Coding::from_frequencies_cloned(*bits_per_fragment, frequencies);
+            coding.internal_nodes_count.len() * bits_per_fragment.get() as usize <= max_code_len as usize
+        }).flat_map(|bits_per_fragment| strategies.iter().filter_map(move |level_sizer| Some((
+            fp::size_estimate::cmap_size_bytes(keys.len(), frequencies, bits_per_fragment.get(), level_sizer)?,
+            bits_per_fragment, *level_sizer
+        )))).collect();
+    combinations.sort_by_key(|(estimated_size, _, _)| *estimated_size);
+    let mut best = None;
+    let mut keys_buffer = keys.to_vec();
+    for (estimated_size, bits_per_fragment, level_sizer) in combinations {
+        if best.as_ref().is_some_and(|(best_size, _)| *best_size <= estimated_size) { break; }
+        let coding = || minimum_redundancy::Coding::from_frequencies_cloned(bits_per_fragment, frequencies);
+        keys_buffer.clone_from_slice(keys);
+        let map = fp::CMap::from_slices_with_coding_conf(&mut keys_buffer, values, coding(),
+            fp::CMapConf::lsize(level_sizer), &mut ());
+        keep_if_smaller_within_latency(&mut best, CompressedMap::CMap(map), keys, max_expected_probes);
+        for (log2_bits_per_group, bits_per_seed) in LATENCY_SEARCH_GROUPS_SEEDS {
+            keys_buffer.clone_from_slice(keys);
+            let goconf = fp::GOConf::bps_bpg(fp::Bits(bits_per_seed), fp::TwoToPowerBits::new(log2_bits_per_group));
+            let map = fp::GOCMap::from_slices_with_coding_conf(&mut keys_buffer, values, coding(),
+                fp::GOCMapConf::groups_lsize_coding(goconf, level_sizer, ()), &mut ());
+            keep_if_smaller_within_latency(&mut best, CompressedMap::GOCMap(map), keys, max_expected_probes);
+        }
+    }
+    best.map(|(_, map)| map)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,4 +218,53 @@ mod tests {
         assert_eq!(bits_to_store_any_of_ref::<u32>([].iter()), 0);
         assert_eq!(bits_to_store_any_of_ref([u64::MAX, 2, 67].iter()), 64);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sweep_size_estimates() {
+        let freq = maplit::hashmap!(0u8 => 50u32, 1 => 20, 2 => 20, 3 => 5, 4 => 5);
+        let strategies = [
+            fp::OptimalLevelSize.into(),
+            fp::ProportionalLevelSize::with_percent(80).into(),
+            fp::ResizedLevel::new(120, fp::OptimalLevelSize).into(),
+        ];
+        let estimates = sweep_size_estimates(10000, &freq, [0, 1, 2, 3, 9], &strategies);
+        assert_eq!(estimates.len(), 9);
+        for ((bits_per_fragment, level_sizer), size) in estimates.iter() {
+            assert!((1..=3).contains(bits_per_fragment));
+            assert!(*size > 0);
+            assert_eq!(*size, fp::size_estimate::cmap_size_bytes(10000, &freq, *bits_per_fragment, level_sizer).unwrap());
+        }
+        let labels: Vec<String> = estimates[..3].iter().map(|((_, level_sizer), _)| level_sizer.to_string()).collect();
+        assert_eq!(labels, ["optimal", "80percent", "optimal_resized120percent"]);
+    }
+
+    #[test]
+    fn test_build_within_latency() {
+        use coding::Coding;
+        let keys: Vec<u32> = (0..2000).collect();
+        let values: Vec<u8> = keys.iter().map(|k| [0, 0, 0, 0, 1, 1, 2, 3][(*k % 8) as usize]).collect();
+        let freq = maplit::hashmap!(0u8 => 1000u32, 1 => 500, 2 => 250, 3 => 250);
+        let map = build_within_latency(&keys, &values, &freq, 2.5, 4).unwrap();
+        let encoder = map.value_coding().encoder();
+        for v in freq.keys() {
+            let bits = map.value_coding().len_of_encoded(&encoder, v) as usize * map.value_coding().bits_per_fragment() as usize;
+            assert!(bits <= 4);
+        }
+        let mut probes = 0usize;
+        for (k, v) in keys.iter().zip(values.iter()) {
+            assert_eq!(map.get_stats(k, &mut probes), Some(v));
+        }
+        assert!(probes as f64 <= 2.5 * keys.len() as f64);
+        assert!(probes <= map.levels() * keys.len());
+        let relaxed = build_within_latency(&keys, &values, &freq, 10.0, 8).unwrap();
+        for (k, v) in keys.iter().zip(values.iter()) {
+            assert_eq!(relaxed.get(k), Some(v));
+        }
+        assert!(build_within_latency(&keys, &values, &freq, 2.5, 1).is_none());
+        assert!(build_within_latency(&keys, &values[1..], &freq, 2.5, 4).is_none());
+        let freq_without_3 = maplit::hashmap!(0u8 => 1000u32, 1 => 500, 2 => 250);
+        assert!(build_within_latency(&keys, &values, &freq_without_3, 2.5, 4).is_none());
+        let freq_with_zero = maplit::hashmap!(0u8 => 1000u32, 1 => 500, 2 => 250, 3 => 0);
+        assert!(build_within_latency(&keys, &values, &freq_with_zero, 2.5, 4).is_none());
+    }
+}
@@ -310,25 +310,25 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
     }
 }
 
-impl<V: Hash+Eq+Clone, S: BuildSeededHasher> CMap<minimum_redundancy::Coding<V>, S> {
+impl<V: Hash+Eq+Clone+Ord, S: BuildSeededHasher> CMap<minimum_redundancy::Coding<V>, S> {
     #[inline(always)]
     pub fn try_from_map_with_conf<K, MS, BM>(map: &HashMap<K, V, MS>, bits_per_fragment: u8, conf: MapConf<BM, S>, bdz_extra_bits_per_fragment: u8) -> Option<Self>
         where K: Hash,
               BM: ValuesPreFiller
     {
         Self::try_from_mapf_with_coding_conf(|| map,
-                                             minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment(bits_per_fragment), map.values()),
+                                             minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment::new(bits_per_fragment)?, map.values()),
                                              conf, bdz_extra_bits_per_fragment)
     }
 
     #[inline(always)]
     pub fn try_from_kv_with_conf<K, BM>(keys: &[K], values: &[V], bits_per_fragment: u8, conf: MapConf<BM, S>, bdz_extra_bits_per_fragment: u8) -> Option<Self>
         where K: Hash, BM: ValuesPreFiller {
-        Self::try_from_kv_with_coding_conf(keys, values, minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment(bits_per_fragment), values.iter()), conf, bdz_extra_bits_per_fragment)
+        Self::try_from_kv_with_coding_conf(keys, values, minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment::new(bits_per_fragment)?, values.iter()), conf, bdz_extra_bits_per_fragment)
     }
 }
 
-impl<V: Hash+Eq+Clone> CMap<minimum_redundancy::Coding<V>> {
+impl<V: Hash+Eq+Clone+Ord> CMap<minimum_redundancy::Coding<V>> {
     #[inline(always)]
     pub fn try_from_map<K: Hash, MS>(map: &HashMap<K, V, MS>, bits_per_fragment: u8, bdz_extra_bits_per_fragment: u8) -> Option<Self> {
         Self::try_from_map_with_conf(map, bits_per_fragment, MapConf::<(), _>::default(), bdz_extra_bits_per_fragment)
@@ -341,7 +341,7 @@ impl<V: Hash+Eq+Clone> CMap<minimum_redundancy::Coding<V>> {
               KvIntoIter: IntoIterator<Item=(&'a K, &'a V)> + 'a,
               FKvIntoIter: Fn() -> KvIntoIter
     {
-        let value_coding = minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment(bits_per_fragment), map().into_iter().map(|(_, v)| v));
+        let value_coding = minimum_redundancy::Coding::<V>::from_iter(BitsPerFragment::new(bits_per_fragment)?, map().into_iter().map(|(_, v)| v));
         Self::try_from_mapf_with_coding(map, value_coding, bdz_extra_bits_per_fragment)
     }
 
@@ -394,6 +394,55 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
     #[inline(always)] pub fn get<K: Hash + ?Sized>(&self, k: &K) -> Option<<<C as Coding>::Decoder<'_> as Decoder>::Decoded> {
         self.get_stats(k, &mut ())
     }
+
+    /// Gets the value associated with the given key `k`, like [`Self::get`],
+    /// and fills `path` with the fragments read at successive levels (the `i`-th element is the fragment read at level `i`).
+    /// So after return, the length of `path` equals the number of levels probed.
+    /// `path` is cleared at the beginning, so it can be reused between calls to avoid allocations.
+    pub fn get_with_path<K: Hash + ?Sized>(&self, k: &K, path: &mut Vec<u32>) -> Option<<<C as Coding>::Decoder<'_> as Decoder>::Decoded> {
+        path.clear();
+        let mut result_decoder = self.value_coding.decoder();
+        let checked = self.value_fragments.bits_per_value != self.value_coding.bits_per_fragment();
+        loop {
+            let fragment = self.value_fragments.get(&(k, path.len() as u8));
+            path.push(fragment as u32);
+            match if checked { result_decoder.consume_checked(fragment as u8) } else { result_decoder.consume(fragment as u8) } {
+                DecodingResult::Value(v) => return Some(v),
+                DecodingResult::Invalid => return None,
+                DecodingResult::Incomplete => {}
+            }
+        }
+    }
+
+    /// Number of keys whose queries are interleaved by [`Self::get_all`].
+    const GET_ALL_BATCH: usize = 16;
+
+    /// Gets the values associated with the given `keys` and stores them in `out`,
+    /// so `out[i]` is set to the value of `keys[i]`, like by [`Self::get`].
+    /// Panics if `keys` and `out` are of different lengths.
+    ///
+    /// The queries of (up to 16) successive keys are interleaved (all of them read the fragment at given level
+    /// before any reads the next one), which allows the processor to overlap their memory accesses.
+    pub fn get_all<'s, K: Hash>(&'s self, keys: &[K], out: &mut [Option<<<C as Coding>::Decoder<'s> as Decoder>::Decoded>]) {
+        assert_eq!(keys.len(), out.len(), "get_all: keys and out are of different lengths");
+        let checked = self.value_fragments.bits_per_value != self.value_coding.bits_per_fragment();
+        let mut pending = Vec::with_capacity(Self::GET_ALL_BATCH);    // indices (in the batch) and decoders of unfinished queries
+        for (keys, out) in keys.chunks(Self::GET_ALL_BATCH).zip(out.chunks_mut(Self::GET_ALL_BATCH)) {
+            pending.extend((0..keys.len()).map(|i| (i, self.value_coding.decoder())));
+            let mut fragment_nr = 0u8;
+            while !pending.is_empty() {
+                pending.retain_mut(|(i, decoder)| {
+                    let fragment = self.value_fragments.get(&(&keys[*i], fragment_nr)) as u8;
+                    match if checked { decoder.consume_checked(fragment) } else { decoder.consume(fragment) } {
+                        DecodingResult::Value(v) => { out[*i] = Some(v); false },
+                        DecodingResult::Invalid => { out[*i] = None; false },
+                        DecodingResult::Incomplete => true
+                    }
+                });
+                fragment_nr += 1;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -411,6 +460,49 @@ mod tests {
         assert_eq!(bdzhmap.value_coding.bits_per_fragment(), bits_per_fragment);
     }
 
+    #[test]
+    fn get_with_path() {
+        let map = hashmap!('a'=>0u8, 'b'=>3u8, 'c'=>8u8, 'd'=>0u8, 'e'=>0u8);
+        let bdzhmap = CMap::try_from_map(&map, 1, 0).unwrap();
+        let mut path = Vec::new();
+        for (k, v) in map.iter() {
+            assert_eq!(bdzhmap.get_with_path(k, &mut path), Some(v));
+            let mut levels = 0usize;
+            bdzhmap.get_stats(k, &mut levels);
+            assert_eq!(path.len(), levels);
+            assert!(path.iter().all(|f| *f <= 1));
+        }
+    }
+
+    #[test]
+    fn get_all() {
+        let keys: Vec<u32> = (0..1000).collect();
+        let values: Vec<u32> = keys.iter().map(|k| [0, 0, 0, 1, 1, 2, 3, 100][*k as usize % 8]).collect();
+        for bdz_extra_bits_per_fragment in [0, 2] {
+            let bdzhmap = CMap::try_from_kv(&keys, &values, 2, bdz_extra_bits_per_fragment).unwrap();
+            let queries: Vec<u32> = (0..1500).collect();    // including keys outside the map
+            let mut out = vec![None; queries.len()];
+            bdzhmap.get_all(&queries, &mut out);
+            for (k, v) in queries.iter().zip(out.iter()) { assert_eq!(*v, bdzhmap.get(k)); }
+            for (v, expected) in out.iter().zip(values.iter()) { assert_eq!(*v, Some(expected)); }
+        }
+    }
+
+    #[test]
+    fn invalid_bits_per_fragment() {
+        let map = hashmap!('a'=>0u8, 'b'=>3u8, 'c'=>8u8);
+        assert!(CMap::try_from_map(&map, 0, 0).is_none());
+        assert!(CMap::try_from_map(&map, 40, 0).is_none());
+        assert!(CMap::try_from_kv(&['a', 'b'], &[0u8, 3], 32, 0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "bits_per_fragment must be in range")]
+    fn invalid_bits_per_fragment_of_builder() {
+        CMap::<minimum_redundancy::Coding<u8>>::try_from_kv_with_builder_conf(&['a', 'b'], &[0u8, 3],
+            &crate::coding::BuildMinimumRedundancy { bits_per_fragment: 40 }, MapConf::<(), _>::default(), 0);
+    }
+
     #[test]
     fn bdzhmap_3pairs_1bpf() {
         bdzhmap_3pairs_conf(MapConf::new(), 1, 0);
@@ -1,5 +1,5 @@
 use csf::coding::{BuildMinimumRedundancy, minimum_redundancy};
-use csf::fp::{OptimalLevelSize, ProportionalLevelSize, ResizedLevel};
+use csf::fp::{OptimalLevelSize, ProportionalLevelSize, ResizedLevel, SoftMaxLevels};
 use ph::BuildSeededHasher;
 use std::fs::File;
 use std::io::Write;
@@ -35,6 +35,15 @@ impl PrintParams for ResizedLevel<OptimalLevelSize> {
     }
 }
 
+impl PrintParams for SoftMaxLevels<OptimalLevelSize> {
+    fn print_params(&self, file: &mut Option<File>) {
+        print!(" optim max~{} levels", self.1);
+        if let Some(ref mut f) = file {
+            write!(f, " true 100").unwrap();
+        }
+    }
+}
+
 impl PrintParams for ProportionalLevelSize {
     fn print_params(&self, file: &mut Option<File>) {
         print!(" levels {}%", self.percent);
@@ -44,6 +53,15 @@ impl PrintParams for ProportionalLevelSize {
     }
 }
 
+impl PrintParams for ResizedLevel<ProportionalLevelSize> {
+    fn print_params(&self, file: &mut Option<File>) {
+        print!(" levels {}%*{}%", self.level_size_chooser.percent, self.percent);
+        if let Some(ref mut f) = file {
+            write!(f, " false {}", self.level_size_chooser.percent as u32 * self.percent as u32 / 100).unwrap();
+        }
+    }
+}
+
 impl<LSC, CSB, S> CSFBuilder for fp::MapConf<LSC, CSB, S>
 where LSC: fp::LevelSizer, CSB: fp::CollisionSolverBuilder, S: BuildSeededHasher
  {
@@ -79,7 +97,7 @@ where LSC: fp::LevelSizer, CSB: fp::CollisionSolverBuilder+fp::IsLossless, S: Bu
     fn new(self, keys: &[u32], values: &[u8], frequencies: &[u32; 256]) -> Self::CSF {
         Self::CSF::from_slices_with_coding_conf(
             keys.to_owned().as_mut(), values,
-            minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment(self.coding.bits_per_fragment), frequencies),
+            minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment::new_unchecked(self.coding.bits_per_fragment), frequencies),
             self,
             &mut ())
     }
@@ -111,7 +129,7 @@ where LSC: fp::LevelSizer, GS: fp::GroupSize, SS: fp::SeedSize, S: BuildSeededHa
     fn new(self, keys: &[u32], values: &[u8], frequencies: &[u32; 256]) -> Self::CSF {
         Self::CSF::from_slices_with_coding_conf(
             keys.to_owned().as_mut(), values,
-            minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment(self.coding.bits_per_fragment), frequencies),
+            minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment::new_unchecked(self.coding.bits_per_fragment), frequencies),
             self,
             &mut ())
     }
@@ -145,7 +163,7 @@ impl CSFBuilder for BuildLSCMap
 
     fn new(self, keys: &[u32], values: &[u8], frequencies: &[u32; 256]) -> Self::CSF {
         Self::CSF::try_from_kv_with_coding_conf(keys, values,
-             minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment(self.0), frequencies),
+             minimum_redundancy::Coding::<u8, _>::from_frequencies_cloned(BitsPerFragment::new_unchecked(self.0), frequencies),
              ls::MapConf::new(),
              0).unwrap()
     }
@@ -164,20 +164,20 @@ mod tests {
     #[test]
     fn code_2bits() {
         let mut code = Code { content: 0b_11_10_01, len: 3 };
-        assert_eq!(code.get_rev(0, BitsPerFragment(2)).unwrap(), 0b01);
-        assert_eq!(code.get_rev(1, BitsPerFragment(2)).unwrap(), 0b10);
-        assert_eq!(code.get_rev(2, BitsPerFragment(2)).unwrap(), 0b11);
-        assert_eq!(code.get(0, BitsPerFragment(2)).unwrap(), 0b11);
-        assert_eq!(code.get(2, BitsPerFragment(2)).unwrap(), 0b01);
-        assert_eq!(code.iter(BitsPerFragment(2)).collect::<Vec<_>>(), [0b11, 0b10, 0b01]);
+        assert_eq!(code.get_rev(0, BitsPerFragment::new_unchecked(2)).unwrap(), 0b01);
+        assert_eq!(code.get_rev(1, BitsPerFragment::new_unchecked(2)).unwrap(), 0b10);
+        assert_eq!(code.get_rev(2, BitsPerFragment::new_unchecked(2)).unwrap(), 0b11);
+        assert_eq!(code.get(0, BitsPerFragment::new_unchecked(2)).unwrap(), 0b11);
+        assert_eq!(code.get(2, BitsPerFragment::new_unchecked(2)).unwrap(), 0b01);
+        assert_eq!(code.iter(BitsPerFragment::new_unchecked(2)).collect::<Vec<_>>(), [0b11, 0b10, 0b01]);
         assert_eq!(code.len, 3);
-        assert_eq!(code.extract_first(BitsPerFragment(2)), Some(0b11));
+        assert_eq!(code.extract_first(BitsPerFragment::new_unchecked(2)), Some(0b11));
         assert_eq!(code.len, 2);
-        assert_eq!(code.extract_first(BitsPerFragment(2)), Some(0b10));
+        assert_eq!(code.extract_first(BitsPerFragment::new_unchecked(2)), Some(0b10));
         assert_eq!(code.len, 1);
-        assert_eq!(code.extract_first(BitsPerFragment(2)), Some(0b01));
+        assert_eq!(code.extract_first(BitsPerFragment::new_unchecked(2)), Some(0b01));
         assert_eq!(code.len, 0);
-        assert_eq!(code.extract_first(BitsPerFragment(2)), None);
+        assert_eq!(code.extract_first(BitsPerFragment::new_unchecked(2)), None);
     }
 
     #[test]
@@ -1,3 +1,4 @@
+use std::iter::FusedIterator;
 use crate::{BitsPerFragment, Coding, DecodingResult, TreeDegree};
 
 /// Decoder that decodes a value for given code, consuming one codeword fragment
@@ -132,3 +133,67 @@ impl<'huff, ValueType, D: TreeDegree> Decoder<'huff, ValueType, D> {
         self.coding.internal_nodes_count[self.level as usize]
     }
 }
+
+/// Iterator over the values decoded from the stream of concatenated codewords.
+///
+/// The stream is a bit vector (slice of 64-bit segments),
+/// in which the fragments of successive codewords are stored one after another, starting from the least significant bits.
+/// Each iteration yields the decoded value and the index of the first bit after its codeword.
+/// Iteration ends after the last complete codeword or after the first invalid codeword.
+///
+/// Returned by [`Coding::decode_stream`].
+pub struct DecodeIterator<'huff, ValueType> {
+    decoder: Decoder<'huff, ValueType>,
+    bits: &'huff [u64],
+    /// Index of the first not decoded bit of `bits`.
+    bit_nr: usize
+}
+
+impl<'huff, ValueType> DecodeIterator<'huff, ValueType> {
+    /// Constructs iterator that decodes, according to `coding`, the codewords stored in `bits`, starting from the bit with index `begin`.
+    pub fn new(coding: &'huff Coding<ValueType>, bits: &'huff [u64], begin: usize) -> Self {
+        Self { decoder: coding.decoder(), bits, bit_nr: begin }
+    }
+
+    /// Returns the index of the first not decoded bit.
+    #[inline] pub fn bit_nr(&self) -> usize { self.bit_nr }
+
+    /// Returns the fragment that begins at bit `self.bit_nr` or [`None`] if the stream has no such many bits.
+    #[inline] fn fragment(&self, bits_per_fragment: u8) -> Option<u32> {
+        if self.bit_nr + bits_per_fragment as usize > self.bits.len() * 64 { return None; }
+        let (segment, offset) = (self.bit_nr / 64, (self.bit_nr % 64) as u32);
+        let mut result = self.bits[segment] >> offset;
+        if offset + bits_per_fragment as u32 > 64 { result |= self.bits[segment + 1] << (64 - offset); }
+        Some((result & ((1u64 << bits_per_fragment) - 1)) as u32)
+    }
+}
+
+impl<'huff, ValueType> FusedIterator for DecodeIterator<'huff, ValueType> {}
+
+impl<'huff, ValueType> Iterator for DecodeIterator<'huff, ValueType> {
+    type Item = (&'huff ValueType, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bits_per_fragment = self.decoder.coding.degree.get();
+        let begin = self.bit_nr;
+        loop {
+            let Some(fragment) = self.fragment(bits_per_fragment) else {
+                self.bit_nr = begin;
+                self.decoder.reset();
+                return None;
+            };
+            self.bit_nr += bits_per_fragment as usize;
+            match self.decoder.consume(fragment) {
+                DecodingResult::Value(v) => {
+                    self.decoder.reset();
+                    return Some((v, self.bit_nr));
+                }
+                DecodingResult::Invalid => {
+                    self.bit_nr = self.bits.len() * 64;
+                    return None;
+                }
+                DecodingResult::Incomplete => {}
+            }
+        }
+    }
+}
@@ -67,7 +67,25 @@ pub trait TreeDegree: Sized + Copy + Mul<u32, Output=u32> {
 /// It is faster than `Degree` and should be preferred
 /// for degrees that are the powers of two.
 #[derive(Copy, Clone)]
-pub struct BitsPerFragment(pub u8);
+pub struct BitsPerFragment(u8);
+
+impl BitsPerFragment {
+    /// The largest supported number of bits per fragment.
+    pub const MAX: u8 = 31;
+
+    /// Returns `BitsPerFragment` for the given number of `bits` per fragment,
+    /// or [`None`] if `bits` is out of range [1, [`Self::MAX`]].
+    #[inline] pub const fn new(bits: u8) -> Option<Self> {
+        if bits >= 1 && bits <= Self::MAX { Some(Self(bits)) } else { None }
+    }
+
+    /// Returns `BitsPerFragment` for the given number of `bits` per fragment, without validating it.
+    /// `bits` must be in range [1, [`Self::MAX`]], otherwise the coding constructed with the result can panic.
+    #[inline(always)] pub const fn new_unchecked(bits: u8) -> Self { Self(bits) }
+
+    /// Returns the number of bits per fragment.
+    #[inline(always)] pub const fn get(self) -> u8 { self.0 }
+}
 
 impl Mul<u32> for BitsPerFragment {
     type Output = u32;
@@ -89,7 +107,8 @@ impl TreeDegree for BitsPerFragment {
     }
 
     fn read(input: &mut dyn std::io::Read) -> std::io::Result<Self> {
-        AsIs::read(input).map(|v| Self(v))
+        Self::new(AsIs::read(input)?).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData,
+            "BitsPerFragment: number of bits per fragment out of range"))
     }
 
     fn get_fragment(&self, bits: u32, fragment_nr: u32) -> u32 {
@@ -141,10 +160,10 @@ impl TryFrom<Degree> for BitsPerFragment {
     type Error = &'static str;
 
     fn try_from(value: Degree) -> Result<Self, Self::Error> {
-        if value.0.is_power_of_two() {  // power of 2?
+        if value.0.is_power_of_two() && value.0 > 1 {  // power of 2 (with at least 1 bit)?
             Ok(Self(value.0.trailing_zeros() as u8))
         } else {
-            Err("BitsPerFragment requires the tree degree to be a power of two")
+            Err("BitsPerFragment requires the tree degree to be a power of two greater than 1")
         }
     }
 }
@@ -302,6 +321,23 @@ mod tests {
         check_degree_8(BitsPerFragment(3));
     }
 
+    #[test]
+    fn bits_per_fragment_new() {
+        assert!(BitsPerFragment::new(0).is_none());
+        assert_eq!(BitsPerFragment::new(1).map(BitsPerFragment::get), Some(1));
+        assert_eq!(BitsPerFragment::new(BitsPerFragment::MAX).map(BitsPerFragment::get), Some(BitsPerFragment::MAX));
+        assert!(BitsPerFragment::new(BitsPerFragment::MAX + 1).is_none());
+        assert!(BitsPerFragment::new(u8::MAX).is_none());
+        let max = BitsPerFragment::new_unchecked(BitsPerFragment::MAX);
+        assert_eq!(max.code_capacity(), 1);
+        assert_eq!(max.get_fragment(u32::MAX, 0), u32::MAX >> 1);
+        assert!(BitsPerFragment::try_from(Degree(1)).is_err());
+        assert_eq!(BitsPerFragment::try_from(Degree(8)).map(BitsPerFragment::get), Ok(3));
+        assert!(BitsPerFragment::read(&mut &[0u8][..]).is_err());
+        assert!(BitsPerFragment::read(&mut &[32u8][..]).is_err());
+        assert_eq!(BitsPerFragment::read(&mut &[5u8][..]).unwrap().get(), 5);
+    }
+
     #[test]
     fn degree_8() {
         check_degree_8(Degree(8));
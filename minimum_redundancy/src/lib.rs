@@ -17,7 +17,7 @@ pub use frequencies::Frequencies;
 mod degree;
 pub use degree::*;
 mod decoder;
-pub use decoder::Decoder;
+pub use decoder::{Decoder, DecodeIterator};
 mod iterators;
 pub use iterators::{CodesIterator, ReversedCodesIterator, LevelIterator};
 
@@ -52,25 +52,64 @@ impl<ValueType: GetSize, D> GetSize for Coding<ValueType, D> {
 
 impl<ValueType, D: TreeDegree> Coding<ValueType, D> {
 
+    /// Orders each run of `values` with equal frequencies (`freq` must be in non-descending order)
+    /// so that [`Self::from_sorted`] places them in ascending order.
+    fn order_ties<W: Weight>(values: &mut [ValueType], freq: &[W]) where ValueType: Ord {
+        let mut begin = 0;
+        while begin < freq.len() {
+            let end = begin + freq[begin..].iter().take_while(|f| **f == freq[begin]).count();
+            values[begin..end].sort_unstable_by(|a, b| b.cmp(a));   // from_sorted reverses values
+            begin = end;
+        }
+    }
+
     /// Constructs coding for given `frequencies` of values and `degree` of the Huffman tree.
-    pub fn from_frequencies<F: Frequencies<Value=ValueType>>(degree: D, frequencies: F) -> Self {
-        let (values, mut freq) = frequencies.into_sorted();
+    ///
+    /// Ties are broken deterministically: values of equal frequencies are ordered ascending,
+    /// so the smaller value is assigned a not longer codeword.
+    /// Therefore, the same frequencies always yield the same coding.
+    pub fn from_frequencies<F: Frequencies<Value=ValueType>>(degree: D, frequencies: F) -> Self
+        where ValueType: Ord
+    {
+        let (mut values, mut freq) = frequencies.into_sorted();
+        Self::order_ties(&mut values, &freq);
         Self::from_sorted(degree, values, &mut freq)
     }
 
     /// Constructs coding for given `frequencies` of values and `degree` of the Huffman tree.
     /// Values are cloned from `frequencies`.
+    ///
+    /// Ties are broken in the same way as in [`Self::from_frequencies`].
     pub fn from_frequencies_cloned<F: Frequencies<Value=ValueType>>(degree: D, frequencies: &F) -> Self
-        where F::Value: Clone
+        where F::Value: Clone + Ord
+    {
+        let (mut values, mut freq) = frequencies.sorted();
+        Self::order_ties(&mut values, &freq);
+        Self::from_sorted(degree, values, &mut freq)
+    }
+
+    /// Constructs coding for given pairs (value, number of its occurrences) and `degree` of the Huffman tree,
+    /// without hashing and sorting.
+    ///
+    /// `values_sorted_desc` must be sorted in non-increasing order of frequencies (which is checked only in debug builds).
+    /// If additionally values of equal frequencies are in ascending order,
+    /// the result is the same as the one of [`Self::from_frequencies`].
+    pub fn from_sorted_frequencies(degree: D, values_sorted_desc: &[(ValueType, u32)]) -> Self
+        where ValueType: Clone
     {
-        let (values, mut freq) = frequencies.sorted();
+        debug_assert!(values_sorted_desc.windows(2).all(|w| w[0].1 >= w[1].1),
+            "from_sorted_frequencies: frequencies must be in non-increasing order");
+        let values = values_sorted_desc.iter().rev().map(|(v, _)| v.clone()).collect();
+        let mut freq: Box<[u32]> = values_sorted_desc.iter().rev().map(|(_, f)| *f).collect();
         Self::from_sorted(degree, values, &mut freq)
     }
 
     /// Counts occurrences of all values exposed by `iter` and constructs coding for obtained
     /// frequencies of values and `degree` of the Huffman tree.
+    ///
+    /// Ties are broken in the same way as in [`Self::from_frequencies`].
     pub fn from_iter<Iter>(degree: D, iter: Iter) -> Self
-        where Iter: IntoIterator, Iter::Item: Borrow<ValueType>, ValueType: Hash + Eq + Clone
+        where Iter: IntoIterator, Iter::Item: Borrow<ValueType>, ValueType: Hash + Eq + Clone + Ord
     {
         Self::from_frequencies(degree, HashMap::<ValueType, usize>::with_occurrences_of(iter))
     }
@@ -83,6 +122,13 @@ impl<ValueType, D: TreeDegree> Coding<ValueType, D> {
         self.levels().map(|(values, _, fragments)| values.len()*fragments as usize).sum()
     }
 
+    /// Returns whether `self` and `other` are equivalent, i.e. assign the same codewords to the same values.
+    pub fn is_equivalent(&self, other: &Self) -> bool where ValueType: PartialEq {
+        self.degree.as_u32() == other.degree.as_u32() &&
+        self.values == other.values &&
+        self.internal_nodes_count == other.internal_nodes_count
+    }
+
     /// Returns decoder that allows for decoding a value.
     #[inline] pub fn decoder(&'_ self) -> Decoder<'_, ValueType, D> {
         return Decoder::<ValueType, D>::new(self);
@@ -284,6 +330,36 @@ impl<ValueType, D: TreeDegree> Coding<ValueType, D> {
         ReversedCodesIterator::<'_, ValueType, D>::new(&self)
     }
 
+    /// Returns the canonical code table: triples (value, codeword length in fragments, codeword content),
+    /// in the order of values in `self`, i.e. sorted by codeword lengths and then by codeword contents.
+    ///
+    /// [`Self::from_code_table`] reconstructs the coding from the table.
+    pub fn code_table(&self) -> Vec<(ValueType, u8, u64)> where ValueType: Clone {
+        self.codes().map(|(v, code)| (v.clone(), code.len as u8, code.content as u64)).collect()
+    }
+
+    /// Reconstructs the coding (of given `degree`) from the canonical code `table`, as returned by [`Self::code_table`].
+    /// The entries of `table` can be given in any order.
+    pub fn from_code_table(table: &[(ValueType, u8, u64)], degree: D) -> Self where ValueType: Clone {
+        let mut sorted: Vec<_> = table.iter().collect();
+        sorted.sort_unstable_by_key(|(_, len, content)| (*len, *content));
+        let max_len = sorted.last().map_or(1, |(_, len, _)| *len as usize).max(1);
+        let mut leaves_count = vec![0u32; max_len + 1];
+        for (_, len, _) in &sorted { leaves_count[*len as usize] += 1; }
+        let mut internal_nodes_count = vec![0u32; max_len].into_boxed_slice();
+        let mut level_size = degree.as_u32();
+        for level in 1..max_len {   // no internal nodes at the last level, where some leaves can be unused
+            let internal_nodes = level_size - leaves_count[level];
+            internal_nodes_count[level - 1] = internal_nodes;
+            level_size = degree * internal_nodes;
+        }
+        Self {
+            values: sorted.into_iter().map(|(v, _, _)| v.clone()).collect(),
+            internal_nodes_count,
+            degree
+        }
+    }
+
     /*pub fn codes(&self) -> impl Iterator<Item=(&ValueType, Code)> {
         self.levels().flat_map(|(values, first_code_bits, fragments)|
             values.iter().enumerate().map(move |(i, v)| {
@@ -293,6 +369,15 @@ impl<ValueType, D: TreeDegree> Coding<ValueType, D> {
     }*/
 }
 
+impl<ValueType> Coding<ValueType> {
+    /// Returns iterator that decodes the codewords stored (one after another, see [`DecodeIterator`]) in `bits`,
+    /// starting from the bit with index `begin`.
+    /// The iterator yields pairs: decoded value, index of the first bit after its codeword.
+    #[inline] pub fn decode_stream<'a>(&'a self, bits: &'a [u64], begin: usize) -> DecodeIterator<'a, ValueType> {
+        DecodeIterator::new(self, bits, begin)
+    }
+}
+
 impl<ValueType: Hash + Eq, D: TreeDegree> Coding<ValueType, D> {
 
     /// Returns a map from (references to) values to the lengths of their codes.
@@ -311,6 +396,29 @@ impl<ValueType: Hash + Eq, D: TreeDegree> Coding<ValueType, D> {
     }
 }
 
+impl<ValueType: Hash + Eq> Coding<ValueType> {
+    /// Returns the total number of bits of the codewords of all value occurrences,
+    /// where `frequencies` maps values to the numbers of their occurrences.
+    /// Values absent from `frequencies` or from `self` are not counted.
+    pub fn total_bits(&self, frequencies: &HashMap<ValueType, u32>) -> u64 {
+        self.codes().map(|(v, c)|
+            frequencies.get(v).map_or(0, |f| *f as u64 * c.len as u64 * self.degree.get() as u64)
+        ).sum()
+    }
+
+    /// Returns the average length (in bits) of the codewords, weighted by the given `frequencies` of values.
+    ///
+    /// Occurrences of values absent from `self` are not counted, neither in the total length (see [`Self::total_bits`])
+    /// nor in the number of occurrences it is divided by.
+    ///
+    /// Comparing the result with the entropy (see [`Frequencies::entropy`]) shows how close to optimal the coding is.
+    pub fn avg_code_len(&self, frequencies: &HashMap<ValueType, u32>) -> f64 {
+        let total_occurrences: u64 = self.codes().map(|(v, _)| frequencies.get(v).map_or(0, |f| *f as u64)).sum();
+        if total_occurrences == 0 { return 0.0; }
+        self.total_bits(frequencies) as f64 / total_occurrences as f64
+    }
+}
+
 impl<ValueType: Hash + Eq + Clone, D: TreeDegree> Coding<ValueType, D> {
     /// Returns a map from (clones of) values to the lengths of their codes.
     pub fn code_lengths(&self) -> HashMap<ValueType, u32> {
@@ -392,6 +500,23 @@ pub fn entropy_to_bpf(entropy: f64) -> u8 {
     (1f64.max(entropy).ceil() as u64 - 1).min(8) as u8
 }
 
+/// Returns the number of bits per fragment, in range [1, `max_bits`] (clamped to [1, [`BitsPerFragment::MAX`]]), that minimizes
+/// the total length (in bits) of the codewords of all value occurrences given by `frequencies`
+/// (i.e. the average code length, see [`Coding::avg_code_len`]).
+///
+/// Ties are broken in favor of larger fragments, as they give fewer fragments per codeword.
+/// This matters since a coding with 1 bit per fragment (Huffman coding) is never worse in bits than the others.
+pub fn best_bits_per_fragment<ValueType>(frequencies: &HashMap<ValueType, u32>, max_bits: u8) -> BitsPerFragment
+    where ValueType: Hash + Eq + Clone + Ord
+{
+    let mut best = (u64::MAX, BitsPerFragment::new_unchecked(1));
+    for bits_per_fragment in (1..=max_bits.clamp(1, BitsPerFragment::MAX)).filter_map(BitsPerFragment::new) {
+        let total_bits = Coding::from_frequencies_cloned(bits_per_fragment, frequencies).total_bits(frequencies);
+        if total_bits <= best.0 { best = (total_bits, bits_per_fragment); }
+    }
+    best.1
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -423,7 +548,7 @@ mod tests {
         //  /  \
         // /\  a
         // bc
-        let huffman = Coding::from_frequencies(BitsPerFragment(1),
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(1),
                                                hashmap!('a' => 100u32, 'b' => 50, 'c' => 10));
         assert_eq!(huffman.total_fragments_count(), 5);
         assert_eq!(huffman.values.as_ref(), ['a', 'b', 'c']);
@@ -452,11 +577,27 @@ mod tests {
         test_read_write(&huffman);
     }
 
+    #[test]
+    fn coding_ties() {
+        let mut forward = HashMap::new();
+        for (i, c) in ('a'..='z').enumerate() { forward.insert(c, 1 + i as u32 % 3); }
+        let mut backward = HashMap::new();
+        for c in ('a'..='z').rev() { backward.insert(c, forward[&c]); }
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(1), forward.clone());
+        assert!(huffman.is_equivalent(&Coding::from_frequencies(BitsPerFragment::new_unchecked(1), backward.clone())));
+        assert!(huffman.is_equivalent(&Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &backward)));
+        assert!(!huffman.is_equivalent(&Coding::from_frequencies(BitsPerFragment::new_unchecked(2), backward)));
+        assert_eq!(&huffman.values[..9], ['c', 'f', 'i', 'l', 'o', 'r', 'u', 'x', 'b']);
+
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(2), hashmap!('c' => 5u32, 'a' => 5, 'b' => 5));
+        assert_eq!(huffman.values.as_ref(), ['a', 'b', 'c']);
+    }
+
     #[test]
     fn coding_3sym_2bits() {
         //  /|\
         //  abc
-        let huffman = Coding::from_frequencies(BitsPerFragment(2),
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(2),
                                                hashmap!('a' => 100u32, 'b' => 50, 'c' => 10));
         assert_eq!(huffman.total_fragments_count(), 3);
         assert_eq!(huffman.values.as_ref(), ['a', 'b', 'c']);
@@ -493,7 +634,7 @@ mod tests {
         // /\ a
         // bc
         let frequencies = hashmap!('d' => 12u32, 'e' => 11, 'f' => 10, 'a' => 3, 'b' => 2, 'c' => 1);
-        let huffman = Coding::from_frequencies(BitsPerFragment(1), frequencies);
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(1), frequencies);
         assert_eq!(huffman.total_fragments_count(), 17);
         assert_eq!(huffman.values.as_ref(), ['d', 'e', 'f', 'a', 'b', 'c']);
         assert_eq!(huffman.internal_nodes_count.as_ref(), [2, 1, 1, 0]);
@@ -549,7 +690,7 @@ mod tests {
         // abc 12 11 10
         // 321
         let frequencies = hashmap!('d' => 12u32, 'e' => 11, 'f' => 10, 'a' => 3, 'b' => 2, 'c' => 1);
-        let huffman = Coding::from_frequencies(BitsPerFragment(2), frequencies);
+        let huffman = Coding::from_frequencies(BitsPerFragment::new_unchecked(2), frequencies);
         assert_eq!(huffman.total_fragments_count(), 9);
         assert_eq!(huffman.values.as_ref(), ['d', 'e', 'f', 'a', 'b', 'c']);
         assert_eq!(huffman.internal_nodes_count.as_ref(), [1, 0]);
@@ -639,4 +780,103 @@ mod tests {
         assert_eq!(huffman.levels().map(|(v, _, _)| v.len()).collect::<Vec<_>>(), &[2, 3]);
         test_read_write(&huffman);
     }
+
+    fn encode_stream(coding: &Coding<char>, text: &str) -> (Vec<u64>, usize) {
+        let codes = coding.codes_for_values();
+        let (mut bits, mut bit_nr) = (Vec::new(), 0);
+        for c in text.chars() {
+            for fragment in codes[&c].iter(coding.degree) {
+                if bit_nr % 64 == 0 { bits.push(0); }
+                bits[bit_nr / 64] |= (fragment as u64) << (bit_nr % 64);
+                if bit_nr % 64 + coding.degree.get() as usize > 64 { bits.push((fragment as u64) >> (64 - bit_nr % 64)); }
+                bit_nr += coding.degree.get() as usize;
+            }
+        }
+        (bits, bit_nr)
+    }
+
+    #[test]
+    fn decode_stream_round_trip() {
+        let text = "abracadabra, a canonical minimum-redundancy code decodes back to the same text";
+        for bits_per_fragment in 1..=5 {
+            let coding = Coding::from_iter(BitsPerFragment::new_unchecked(bits_per_fragment), text.chars());
+            let (bits, len) = encode_stream(&coding, text);
+            let decoded: Vec<_> = coding.decode_stream(&bits, 0).take(text.len()).collect();
+            assert_eq!(decoded.iter().map(|(c, _)| **c).collect::<String>(), text, "bits_per_fragment={bits_per_fragment}");
+            assert_eq!(decoded.last().unwrap().1, len);
+            let second_begin = decoded[0].1;
+            assert_eq!(coding.decode_stream(&bits, second_begin).next(), Some(decoded[1]));
+        }
+    }
+
+    fn code_table_round_trip<D: TreeDegree>(degree: D, frequencies: &HashMap<char, u32>) {
+        let coding = Coding::from_frequencies_cloned(degree, frequencies);
+        let mut table = coding.code_table();
+        assert_eq!(table.len(), frequencies.len());
+        table.reverse();
+        let reconstructed = Coding::from_code_table(&table, degree);
+        assert!(reconstructed.is_equivalent(&coding), "degree={}", degree.as_u32());
+        assert_eq!(reconstructed.codes_for_values(), coding.codes_for_values());
+    }
+
+    #[test]
+    fn code_table() {
+        let frequencies = hashmap!('d' => 12u32, 'e' => 11, 'a' => 3, 'b' => 2, 'c' => 1);
+        let coding = Coding::from_frequencies_cloned(Degree(3), &frequencies);
+        assert_eq!(coding.code_table(), [('d', 1, 1), ('e', 1, 2), ('a', 2, 0), ('b', 2, 1), ('c', 2, 2)]);
+        code_table_round_trip(Degree(3), &frequencies);
+        let frequencies: HashMap<char, u32> = ('a'..='z').zip([1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 1, 1, 1, 2, 2, 9, 7, 100, 4, 4, 4, 5, 6, 6, 1, 3]).collect();
+        for bits_per_fragment in 1..=4 { code_table_round_trip(BitsPerFragment::new_unchecked(bits_per_fragment), &frequencies); }
+        for degree in [3, 4, 5, 7] { code_table_round_trip(Degree(degree), &frequencies); }
+        code_table_round_trip(BitsPerFragment::new_unchecked(2), &hashmap!('x' => 5u32));
+        code_table_round_trip(BitsPerFragment::new_unchecked(1), &HashMap::new());
+    }
+
+    #[test]
+    fn sorted_frequencies() {
+        let frequencies: HashMap<char, u32> = ('a'..='z').zip([1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 1, 1, 1, 2, 2, 9, 7, 100, 4, 4, 4, 5, 6, 6, 1, 3]).collect();
+        let mut sorted: Vec<(char, u32)> = frequencies.iter().map(|(v, f)| (*v, *f)).collect();
+        sorted.sort_unstable_by(|(v1, f1), (v2, f2)| f2.cmp(f1).then(v1.cmp(v2)));
+        for bits_per_fragment in 1..=3 {
+            let expected = Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(bits_per_fragment), &frequencies);
+            assert!(Coding::from_sorted_frequencies(BitsPerFragment::new_unchecked(bits_per_fragment), &sorted).is_equivalent(&expected));
+        }
+        let expected = Coding::from_frequencies_cloned(Degree(5), &frequencies);
+        assert!(Coding::from_sorted_frequencies(Degree(5), &sorted).is_equivalent(&expected));
+        let small = [('x', 5), ('y', 2)];
+        assert!(Coding::from_sorted_frequencies(BitsPerFragment::new_unchecked(2), &small)
+            .is_equivalent(&Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(2), &hashmap!('x' => 5u32, 'y' => 2))));
+    }
+
+    #[test]
+    fn best_bpf() {
+        let uniform = |n: u32| (0..n).map(|v| (v, 10u32)).collect::<HashMap<_, _>>();
+        assert_eq!(best_bits_per_fragment(&uniform(4), 8).get(), 2);
+        assert_eq!(best_bits_per_fragment(&uniform(16), 8).get(), 4);
+        assert_eq!(best_bits_per_fragment(&uniform(16), 3).get(), 2);
+        assert_eq!(best_bits_per_fragment(&uniform(256), 8).get(), 8);
+        // a dominating value makes short codewords (and so small fragments) pay off:
+        let skewed = hashmap!('a' => 1000u32, 'b' => 10, 'c' => 10, 'd' => 10);
+        assert_eq!(best_bits_per_fragment(&skewed, 8).get(), 1);
+        assert_eq!(best_bits_per_fragment(&HashMap::<char, u32>::new(), 4).get(), 4);
+        assert_eq!(best_bits_per_fragment(&skewed, 0).get(), 1);
+        assert_eq!(best_bits_per_fragment(&skewed, u8::MAX).get(), 1);
+        assert_eq!(best_bits_per_fragment(&HashMap::<char, u32>::new(), u8::MAX).get(), BitsPerFragment::MAX);
+    }
+
+    #[test]
+    fn code_len_statistics() {
+        let frequencies = hashmap!('a' => 4u32, 'b' => 2, 'c' => 1, 'd' => 1);
+        let coding = Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(1), &frequencies);
+        assert_eq!(coding.total_bits(&frequencies), 14);  // lengths: a=1, b=2, c=3, d=3
+        assert_eq!(coding.avg_code_len(&frequencies), 1.75);
+        assert_eq!(coding.avg_code_len(&frequencies), frequencies.entropy());
+        let coding = Coding::from_frequencies_cloned(BitsPerFragment::new_unchecked(2), &frequencies);
+        assert_eq!(coding.total_bits(&frequencies), 16);
+        assert_eq!(coding.avg_code_len(&frequencies), 2.0);
+        assert_eq!(coding.avg_code_len(&HashMap::new()), 0.0);
+        let with_uncoded = hashmap!('a' => 4u32, 'b' => 2, 'c' => 1, 'd' => 1, 'e' => 8);
+        assert_eq!(coding.total_bits(&with_uncoded), 16);
+        assert_eq!(coding.avg_code_len(&with_uncoded), 2.0);
+    }
 }
@@ -7,6 +7,11 @@ pub trait BuildStatsCollector {
     /// Called once at each level to indicate sizes of input and level. Default implementation do nothing.
     #[inline(always)] fn level(&mut self, _input_size: usize, _level_size: usize) {}
 
+    /// Called once at each level, after its construction, with the index of the level (counting from 0),
+    /// the sizes of input and level, and the number of keys resolved (i.e. removed from the input) at the level.
+    /// Default implementation do nothing.
+    #[inline(always)] fn level_resolved(&mut self, _level_nr: usize, _input_size: usize, _resolved_keys: usize, _level_size: usize) {}
+
     /// Called once at the end of the building process with number of remaining (unsupported) keys (0 when construction has been successful).
     /// Default implementation do nothing.
     #[inline(always)] fn end(&mut self, _remaining_keys: usize) {}